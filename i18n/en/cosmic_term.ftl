@@ -10,9 +10,35 @@ git-description = Git commit {$hash} on {$date}
 color-schemes = Color schemes
 rename = Rename
 export = Export
+export-as = Export as {$format}
 delete = Delete
 import = Import
 import-errors = Import errors
+preview = Preview
+derive-bright-colors = Derive bright colors
+derive-dim-colors = Derive dim colors
+colors = Colors
+foreground = Foreground
+background = Background
+cursor = Cursor
+bright-foreground = Bright foreground
+dim-foreground = Dim foreground
+normal-colors = Normal colors
+bright-colors = Bright colors
+dim-colors = Dim colors
+hex = Hex
+rgb = RGB
+recent-colors = Recent colors
+copy-to-clipboard = Copy to clipboard
+copied-to-clipboard = Copied to clipboard
+
+## Tab color scheme
+tab-color-scheme = Tab color scheme
+profile-color-scheme = Use profile color scheme
+program-colors = Program colors
+program-colors-description = Colors changed by programs in this tab. Keep them to restore them after programs reset them.
+keep = Keep
+reset = Reset
 
 ## Profiles
 profiles = Profiles
@@ -21,11 +47,47 @@ command-line = Command line
 tab-title = Tab title
 tab-title-description = Override the default tab title
 add-profile = Add profile
+export-all = Export all
+ssh-hosts = SSH hosts
+pin = Pin
 new-profile = New profile
 make-default = Make default
 working-directory = Working directory
-hold = Hold
-remain-open = Remain open after child process exits.
+login-shell = Login shell
+login-shell-description = Start the shell as a login shell when no command is set.
+initial-input = Initial input
+initial-input-description = Typed into the shell once it shows its first prompt.
+close-on-exit = Close on exit
+never-close = Never
+always-close = Always
+close-on-success = If the process exited successfully
+color-scheme-mode = Color scheme
+match-app-theme = Match app theme
+always-dark = Always dark
+always-light = Always light
+custom-opacity = Custom background opacity
+line-height = Line height
+padding = Padding
+scrollback = Scrollback
+scrollback-description = Lines kept for scrolling back, more lines use more memory.
+scrollback-lines = { $lines } lines
+default = Default
+environment = Environment variables
+environment-description = Values can refer to other variables with $VAR or ${VAR}.
+variable = Variable
+value = Value
+unset = Unset
+add-variable = Add variable
+switch-rules = Automatic switching
+switch-rules-description = Use this profile while the shell reports a matching host, user or directory. Empty fields match anything.
+host = Host
+user = User
+directory = Directory
+add-rule = Add rule
+process-exited = Process exited
+process-exited-status = Process exited with status { $status }
+restart = Restart
+close = Close
 
 ## Settings
 settings = Settings
@@ -38,8 +100,21 @@ dark = Dark
 light = Light
 syntax-dark = Color scheme dark
 syntax-light = Color scheme light
+desktop-color-scheme = Auto (desktop)
 default-zoom-step = Zoom steps
 opacity = Background opacity
+background-image = Background image
+background-image-mode = Image placement
+background-image-dim = Image dimming
+scaled = Scaled
+tiled = Tiled
+centered = Centered
+choose = Choose...
+remove = Remove
+none = None
+minimum-contrast = Minimum contrast
+minimum-contrast-description = Adjust text colors that are hard to read against their background.
+off = Off
 
 ### Font
 font = Font
@@ -71,9 +146,13 @@ find-next = Find next
 ## File
 file = File
 new-tab = New tab
+open-profile = Open profile
+search-profiles = Search profiles
+no-profiles-found = No profiles found
 new-window = New window
 profile = Profile
 menu-profiles = Profiles...
+restart-tab = Restart tab
 close-tab = Close tab
 quit = Quit
 
@@ -83,6 +162,7 @@ copy = Copy
 paste = Paste
 select-all = Select all
 find = Find
+copy-mode = Copy mode
 
 ## View
 view = View
@@ -94,6 +174,7 @@ previous-tab = Previous tab
 split-horizontal = Split horizontal
 split-vertical = Split vertical
 pane-toggle-maximize = Toggle maximized
+menu-tab-color-scheme = Tab color scheme...
 menu-color-schemes = Color schemes...
 menu-settings = Settings...
 menu-about = About COSMIC Terminal...
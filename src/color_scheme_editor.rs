@@ -0,0 +1,339 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use alacritty_terminal::{term::color::Colors, vte::ansi::NamedColor};
+use cosmic::{
+    cosmic_theme,
+    iced::{Alignment, Background, Color, Font, Length},
+    iced_core::Border,
+    style, theme,
+    widget::{self, ColorPickerModel},
+    Element,
+};
+use hex_color::HexColor;
+
+use crate::{
+    config::{ColorScheme, ColorSchemeId, ColorSchemeKind},
+    fl, icon_cache_get, Message,
+};
+
+/// A color of a color scheme that can be edited
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColorSchemeSlot {
+    Foreground,
+    Background,
+    Cursor,
+    BrightForeground,
+    DimForeground,
+    Normal(usize),
+    Bright(usize),
+    Dim(usize),
+}
+
+impl ColorSchemeSlot {
+    /// Index of the slot in terminal colors, which include derived colors
+    fn index(self) -> usize {
+        match self {
+            Self::Foreground => NamedColor::Foreground as usize,
+            Self::Background => NamedColor::Background as usize,
+            Self::Cursor => NamedColor::Cursor as usize,
+            Self::BrightForeground => NamedColor::BrightForeground as usize,
+            Self::DimForeground => NamedColor::DimForeground as usize,
+            Self::Normal(index) => NamedColor::Black as usize + index,
+            Self::Bright(index) => NamedColor::BrightBlack as usize + index,
+            Self::Dim(index) => NamedColor::DimBlack as usize + index,
+        }
+    }
+
+    pub fn set(self, color_scheme: &mut ColorScheme, color: Option<HexColor>) {
+        match self {
+            Self::Foreground => color_scheme.foreground = color,
+            Self::Background => color_scheme.background = color,
+            Self::Cursor => color_scheme.cursor = color,
+            Self::BrightForeground => color_scheme.bright_foreground = color,
+            Self::DimForeground => color_scheme.dim_foreground = color,
+            Self::Normal(index) => color_scheme.normal.set(index, color),
+            Self::Bright(index) => color_scheme.bright.set(index, color),
+            Self::Dim(index) => color_scheme.dim.set(index, color),
+        }
+    }
+
+    fn color(self, colors: &Colors) -> Option<Color> {
+        colors[self.index()].map(|rgb| Color::from_rgb8(rgb.r, rgb.g, rgb.b))
+    }
+}
+
+/// State of the color scheme editor, the color scheme itself is edited in place in the config
+pub struct ColorSchemeEditor {
+    pub kind: ColorSchemeKind,
+    pub id: ColorSchemeId,
+    pub slot: ColorSchemeSlot,
+    pub picker: ColorPickerModel,
+    /// The color scheme was changed in the config, but not saved yet
+    pub unsaved: bool,
+}
+
+impl ColorSchemeEditor {
+    pub fn new(kind: ColorSchemeKind, id: ColorSchemeId, color_scheme: &ColorScheme) -> Self {
+        let slot = ColorSchemeSlot::Foreground;
+        Self {
+            kind,
+            id,
+            slot,
+            picker: picker(slot.color(&Colors::from(color_scheme))),
+            unsaved: false,
+        }
+    }
+
+    pub fn select(&mut self, slot: ColorSchemeSlot, color_scheme: &ColorScheme) {
+        self.slot = slot;
+        self.picker = picker(slot.color(&Colors::from(color_scheme)));
+    }
+
+    /// Store the color applied in the color picker in the selected slot
+    pub fn apply(&self, color_scheme: &mut ColorScheme) {
+        if let Some(color) = self.picker.get_applied_color() {
+            let [r, g, b, _] = color.into_rgba8();
+            self.slot.set(color_scheme, Some(HexColor::rgb(r, g, b)));
+        }
+    }
+
+    pub fn derive_brights(color_scheme: &ColorScheme) -> bool {
+        color_scheme.bright.is_empty() && color_scheme.bright_foreground.is_none()
+    }
+
+    pub fn derive_dims(color_scheme: &ColorScheme) -> bool {
+        color_scheme.dim.is_empty() && color_scheme.dim_foreground.is_none()
+    }
+
+    /// Toggle deriving bright colors, keeping the derived colors when derivation is disabled
+    pub fn set_derive_brights(color_scheme: &mut ColorScheme, derive: bool) {
+        let slots = std::iter::once(ColorSchemeSlot::BrightForeground)
+            .chain((0..8).map(ColorSchemeSlot::Bright));
+        set_derive(color_scheme, slots, derive);
+    }
+
+    /// Toggle deriving dim colors, keeping the derived colors when derivation is disabled
+    pub fn set_derive_dims(color_scheme: &mut ColorScheme, derive: bool) {
+        let slots =
+            std::iter::once(ColorSchemeSlot::DimForeground).chain((0..8).map(ColorSchemeSlot::Dim));
+        set_derive(color_scheme, slots, derive);
+    }
+
+    pub fn view<'a>(
+        &'a self,
+        color_scheme: &'a ColorScheme,
+        spacing: cosmic_theme::Spacing,
+    ) -> Element<'a, Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_s, ..
+        } = spacing;
+
+        let colors = Colors::from(color_scheme);
+        let derive_brights = Self::derive_brights(color_scheme);
+        let derive_dims = Self::derive_dims(color_scheme);
+
+        let swatch = |slot: ColorSchemeSlot, enabled: bool| -> Element<'a, Message> {
+            let color = slot.color(&colors);
+            let selected = slot == self.slot;
+            let container =
+                widget::container(widget::Space::new(Length::Fixed(24.0), Length::Fixed(24.0)))
+                    .style(theme::Container::custom(move |theme| {
+                        let cosmic = theme.cosmic();
+                        widget::container::Appearance {
+                            background: color.map(Background::Color),
+                            border: Border {
+                                radius: 4.0.into(),
+                                width: if selected { 2.0 } else { 1.0 },
+                                color: if selected {
+                                    cosmic.accent_color().into()
+                                } else {
+                                    cosmic.background.component.divider.into()
+                                },
+                            },
+                            ..Default::default()
+                        }
+                    }));
+            widget::button(container)
+                .padding(0)
+                .style(style::Button::Icon)
+                .on_press_maybe(enabled.then_some(Message::ColorSchemeEditSlot(slot)))
+                .into()
+        };
+        let swatch_row = |slot: fn(usize) -> ColorSchemeSlot, enabled: bool| {
+            widget::row::with_children((0..8).map(|index| swatch(slot(index), enabled)).collect())
+                .spacing(space_xxs)
+        };
+
+        let header = widget::row::with_children(vec![
+            widget::button(icon_cache_get("go-previous-symbolic", 16))
+                .on_press(Message::ColorSchemeEditClose)
+                .style(style::Button::Icon)
+                .into(),
+            widget::text::heading(&color_scheme.name).into(),
+        ])
+        .align_items(Alignment::Center)
+        .spacing(space_xxs);
+
+        let preview_section =
+            widget::settings::view_section(fl!("preview")).add(preview(&colors, spacing));
+
+        let derive_section = widget::settings::view_section("")
+            .add(
+                widget::settings::item::builder(fl!("derive-bright-colors"))
+                    .toggler(derive_brights, Message::ColorSchemeEditDeriveBright),
+            )
+            .add(
+                widget::settings::item::builder(fl!("derive-dim-colors"))
+                    .toggler(derive_dims, Message::ColorSchemeEditDeriveDim),
+            );
+
+        let colors_section = widget::settings::view_section(fl!("colors"))
+            .add(widget::settings::item(
+                fl!("foreground"),
+                swatch(ColorSchemeSlot::Foreground, true),
+            ))
+            .add(widget::settings::item(
+                fl!("background"),
+                swatch(ColorSchemeSlot::Background, true),
+            ))
+            .add(widget::settings::item(
+                fl!("cursor"),
+                swatch(ColorSchemeSlot::Cursor, true),
+            ))
+            .add(widget::settings::item(
+                fl!("bright-foreground"),
+                swatch(ColorSchemeSlot::BrightForeground, !derive_brights),
+            ))
+            .add(widget::settings::item(
+                fl!("dim-foreground"),
+                swatch(ColorSchemeSlot::DimForeground, !derive_dims),
+            ))
+            .add(widget::settings::item(
+                fl!("normal-colors"),
+                swatch_row(ColorSchemeSlot::Normal, true),
+            ))
+            .add(widget::settings::item(
+                fl!("bright-colors"),
+                swatch_row(ColorSchemeSlot::Bright, !derive_brights),
+            ))
+            .add(widget::settings::item(
+                fl!("dim-colors"),
+                swatch_row(ColorSchemeSlot::Dim, !derive_dims),
+            ));
+
+        let picker = self
+            .picker
+            .builder(Message::ColorSchemeEditPicker)
+            .width(Length::Fixed(248.0))
+            .height(Length::Fixed(158.0))
+            .build(
+                fl!("recent-colors"),
+                fl!("copy-to-clipboard"),
+                fl!("copied-to-clipboard"),
+            );
+
+        widget::column::with_children(vec![
+            header.into(),
+            preview_section.into(),
+            derive_section.into(),
+            colors_section.into(),
+            widget::container(picker)
+                .width(Length::Fill)
+                .center_x()
+                .into(),
+        ])
+        .spacing(space_s)
+        .into()
+    }
+}
+
+fn picker(color_opt: Option<Color>) -> ColorPickerModel {
+    ColorPickerModel::new(fl!("hex"), fl!("rgb"), None, color_opt)
+}
+
+fn set_derive(
+    color_scheme: &mut ColorScheme,
+    slots: impl Iterator<Item = ColorSchemeSlot>,
+    derive: bool,
+) {
+    // Derived colors are calculated when converting to terminal colors
+    let colors = Colors::from(&*color_scheme);
+    for slot in slots {
+        let color_opt = if derive {
+            None
+        } else {
+            colors[slot.index()].map(|rgb| HexColor::rgb(rgb.r, rgb.g, rgb.b))
+        };
+        slot.set(color_scheme, color_opt);
+    }
+}
+
+// Sample terminal output rendered with the color scheme
+fn preview<'a>(colors: &Colors, spacing: cosmic_theme::Spacing) -> Element<'a, Message> {
+    let cosmic_theme::Spacing { space_xs, .. } = spacing;
+
+    let color = |index: usize| -> Color {
+        colors[index]
+            .map(|rgb| Color::from_rgb8(rgb.r, rgb.g, rgb.b))
+            .unwrap_or(Color::WHITE)
+    };
+    let span = |text: &str, index: usize| -> Element<'a, Message> {
+        widget::text(text.to_string())
+            .font(Font::MONOSPACE)
+            .style(style::Text::Color(color(index)))
+            .into()
+    };
+    let foreground = NamedColor::Foreground as usize;
+    let prompt = || {
+        vec![
+            span("user@cosmic", NamedColor::Green as usize),
+            span(":", foreground),
+            span("~", NamedColor::Blue as usize),
+            span("$ ", foreground),
+        ]
+    };
+    let palette = |first: usize| {
+        widget::row::with_children((first..first + 8).map(|index| span("██", index)).collect())
+    };
+
+    let mut ls = prompt();
+    ls.push(span("ls", foreground));
+    let mut cursor = prompt();
+    cursor.push(span("█", NamedColor::Cursor as usize));
+
+    let background_opt =
+        colors[NamedColor::Background].map(|rgb| Color::from_rgb8(rgb.r, rgb.g, rgb.b));
+    widget::container(widget::column::with_children(vec![
+        widget::row::with_children(ls).into(),
+        widget::row::with_children(vec![
+            span("Documents  ", NamedColor::Blue as usize),
+            span("script.sh  ", NamedColor::Green as usize),
+            span("link  ", NamedColor::Cyan as usize),
+            span("notes.txt", foreground),
+        ])
+        .into(),
+        span("Dimmed text", NamedColor::DimForeground as usize),
+        span("Bright text", NamedColor::BrightForeground as usize),
+        palette(NamedColor::Black as usize).into(),
+        palette(NamedColor::BrightBlack as usize).into(),
+        palette(NamedColor::DimBlack as usize).into(),
+        widget::row::with_children(cursor).into(),
+    ]))
+    .padding(space_xs)
+    .width(Length::Fill)
+    .style(theme::Container::custom(move |theme| {
+        let cosmic = theme.cosmic();
+        widget::container::Appearance {
+            // Color schemes without a background use the window background
+            background: Some(Background::Color(
+                background_opt.unwrap_or_else(|| cosmic.background.base.into()),
+            )),
+            border: Border {
+                radius: 8.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }))
+    .into()
+}
@@ -0,0 +1,1429 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use hex_color::HexColor;
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::config::{ColorScheme, ColorSchemeAnsi};
+
+/// File formats that color schemes can be imported from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColorSchemeFormat {
+    /// Alacritty TOML configuration
+    Alacritty,
+    /// Legacy Alacritty YAML configuration
+    AlacrittyYaml,
+    /// base16 scheme YAML
+    Base16,
+    /// iTerm2 `.itermcolors` property list
+    ITerm2,
+    /// kitty `.conf` theme
+    Kitty,
+    /// COSMIC Terminal RON color scheme
+    Ron,
+    /// Windows Terminal JSON scheme or settings
+    WindowsTerminal,
+    /// X resources, as used by xterm and rxvt
+    Xresources,
+}
+
+impl ColorSchemeFormat {
+    /// Formats that color schemes can be exported to
+    pub const EXPORT: [Self; 6] = [
+        Self::Ron,
+        Self::Alacritty,
+        Self::Kitty,
+        Self::WindowsTerminal,
+        Self::ITerm2,
+        Self::Xresources,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Alacritty => "Alacritty",
+            Self::AlacrittyYaml => "Alacritty YAML",
+            Self::Base16 => "base16",
+            Self::ITerm2 => "iTerm2",
+            Self::Kitty => "kitty",
+            Self::Ron => "COSMIC Terminal",
+            Self::WindowsTerminal => "Windows Terminal",
+            Self::Xresources => "Xresources",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Alacritty => "toml",
+            Self::AlacrittyYaml | Self::Base16 => "yaml",
+            Self::ITerm2 => "itermcolors",
+            Self::Kitty => "conf",
+            Self::Ron => "ron",
+            Self::WindowsTerminal => "json",
+            Self::Xresources => "Xresources",
+        }
+    }
+
+    /// Detect the format of a color scheme file from its extension, falling back to its contents
+    pub fn detect(path: &Path, contents: &str) -> Option<Self> {
+        let file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "ron" => return Some(Self::Ron),
+            "itermcolors" => return Some(Self::ITerm2),
+            "toml" => return Some(Self::Alacritty),
+            "yml" | "yaml" => {
+                let map = parse_yaml(contents);
+                return Some(
+                    if map.contains_key("base00") || map.contains_key("palette.base00") {
+                        Self::Base16
+                    } else {
+                        Self::AlacrittyYaml
+                    },
+                );
+            }
+            "conf" => return Some(Self::Kitty),
+            "json" => return Some(Self::WindowsTerminal),
+            "xresources" | "xdefaults" => return Some(Self::Xresources),
+            _ => {}
+        }
+        if file_name.starts_with(".xresources") || file_name.starts_with(".xdefaults") {
+            return Some(Self::Xresources);
+        }
+
+        let trimmed = contents.trim_start();
+        if trimmed.starts_with("<?xml") || trimmed.starts_with("<plist") {
+            Some(Self::ITerm2)
+        } else if trimmed.starts_with('{') {
+            Some(Self::WindowsTerminal)
+        } else if trimmed.starts_with('(') {
+            Some(Self::Ron)
+        } else if contents.contains("[colors") {
+            Some(Self::Alacritty)
+        } else if contents.contains("base00") {
+            Some(Self::Base16)
+        } else if contents.lines().any(|line| {
+            let line = line.trim_start();
+            line.starts_with('*') || line.contains("color0:")
+        }) {
+            Some(Self::Xresources)
+        } else if contents
+            .lines()
+            .any(|line| line.trim_start().starts_with("color0 "))
+        {
+            Some(Self::Kitty)
+        } else if contents.contains("colors:") {
+            Some(Self::AlacrittyYaml)
+        } else {
+            None
+        }
+    }
+
+    /// Parse color schemes from file contents, a single file can contain multiple schemes
+    pub fn parse(self, contents: &str) -> Result<Vec<ColorScheme>, String> {
+        match self {
+            Self::Alacritty => {
+                let value: toml::Value = toml::from_str(contents).map_err(|err| err.to_string())?;
+                let mut map = BTreeMap::new();
+                flatten_toml(String::new(), &value, &mut map);
+                Ok(vec![alacritty(&map)?])
+            }
+            Self::AlacrittyYaml => Ok(vec![alacritty(&parse_yaml(contents))?]),
+            Self::Base16 => Ok(vec![base16(&parse_yaml(contents))?]),
+            Self::ITerm2 => Ok(vec![iterm2(contents)?]),
+            Self::Kitty => Ok(vec![kitty(contents)?]),
+            Self::Ron => {
+                let color_scheme: ColorScheme =
+                    ron::de::from_str(contents).map_err(|err| err.to_string())?;
+                Ok(vec![color_scheme])
+            }
+            Self::WindowsTerminal => windows_terminal(contents),
+            Self::Xresources => Ok(vec![xresources(contents)?]),
+        }
+    }
+
+    /// Serialize a color scheme, colors the format does not support are left out
+    pub fn export(self, color_scheme: &ColorScheme) -> Result<String, String> {
+        let exported = match self {
+            Self::Alacritty => export_alacritty(color_scheme),
+            Self::ITerm2 => export_iterm2(color_scheme),
+            Self::Kitty => export_kitty(color_scheme),
+            Self::Ron => ron::ser::to_string_pretty(color_scheme, ron::ser::PrettyConfig::new())
+                .map_err(|err| err.to_string())?,
+            Self::WindowsTerminal => export_windows_terminal(color_scheme)?,
+            Self::Xresources => export_xresources(color_scheme),
+            Self::AlacrittyYaml | Self::Base16 => {
+                return Err(format!("exporting to {} is not supported", self.name()));
+            }
+        };
+
+        Ok(exported)
+    }
+}
+
+fn hex(color: HexColor) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b)
+}
+
+fn ansi_iter(ansi: &ColorSchemeAnsi) -> [(&'static str, Option<HexColor>); 8] {
+    [
+        ("black", ansi.black),
+        ("red", ansi.red),
+        ("green", ansi.green),
+        ("yellow", ansi.yellow),
+        ("blue", ansi.blue),
+        ("magenta", ansi.magenta),
+        ("cyan", ansi.cyan),
+        ("white", ansi.white),
+    ]
+}
+
+// Iterate over the 16 color palette with indexes
+fn indexed_iter(color_scheme: &ColorScheme) -> impl Iterator<Item = (usize, HexColor)> {
+    ansi_iter(&color_scheme.normal)
+        .into_iter()
+        .chain(ansi_iter(&color_scheme.bright))
+        .enumerate()
+        .filter_map(|(index, (_, color_opt))| Some((index, color_opt?)))
+}
+
+fn export_alacritty(color_scheme: &ColorScheme) -> String {
+    let mut exported = String::new();
+    let mut section = |name: &str, colors: &[(&str, Option<HexColor>)]| {
+        if colors.iter().all(|(_, color_opt)| color_opt.is_none()) {
+            return;
+        }
+        if !exported.is_empty() {
+            exported.push('\n');
+        }
+        exported.push_str(&format!("[colors.{name}]\n"));
+        for (key, color_opt) in colors {
+            if let Some(color) = color_opt {
+                exported.push_str(&format!("{key} = \"{}\"\n", hex(*color)));
+            }
+        }
+    };
+    section(
+        "primary",
+        &[
+            ("foreground", color_scheme.foreground),
+            ("background", color_scheme.background),
+            ("bright_foreground", color_scheme.bright_foreground),
+            ("dim_foreground", color_scheme.dim_foreground),
+        ],
+    );
+    section("cursor", &[("cursor", color_scheme.cursor)]);
+    section("normal", &ansi_iter(&color_scheme.normal));
+    section("bright", &ansi_iter(&color_scheme.bright));
+    section("dim", &ansi_iter(&color_scheme.dim));
+    section(
+        "selection",
+        &[
+            ("text", color_scheme.selection_foreground),
+            ("background", color_scheme.selection_background),
+        ],
+    );
+    section(
+        "search.matches",
+        &[
+            ("foreground", color_scheme.search_match_foreground),
+            ("background", color_scheme.search_match_background),
+        ],
+    );
+    section(
+        "search.focused_match",
+        &[
+            ("foreground", color_scheme.focused_match_foreground),
+            ("background", color_scheme.focused_match_background),
+        ],
+    );
+    exported
+}
+
+fn export_iterm2(color_scheme: &ColorScheme) -> String {
+    let mut exported = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" ",
+        "\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+        "<plist version=\"1.0\">\n",
+        "<dict>\n",
+    ));
+    let mut color = |key: &str, color: HexColor| {
+        exported.push_str(&format!("\t<key>{key}</key>\n\t<dict>\n"));
+        for (component, value) in [("Blue", color.b), ("Green", color.g), ("Red", color.r)] {
+            exported.push_str(&format!(
+                "\t\t<key>{component} Component</key>\n\t\t<real>{}</real>\n",
+                f32::from(value) / 255.0
+            ));
+        }
+        exported.push_str("\t\t<key>Color Space</key>\n\t\t<string>sRGB</string>\n\t</dict>\n");
+    };
+    for (index, ansi_color) in indexed_iter(color_scheme) {
+        color(&format!("Ansi {index} Color"), ansi_color);
+    }
+    for (key, color_opt) in [
+        ("Background Color", color_scheme.background),
+        ("Bold Color", color_scheme.bright_foreground),
+        ("Cursor Color", color_scheme.cursor),
+        ("Foreground Color", color_scheme.foreground),
+    ] {
+        if let Some(special_color) = color_opt {
+            color(key, special_color);
+        }
+    }
+    exported.push_str("</dict>\n</plist>\n");
+    exported
+}
+
+fn export_kitty(color_scheme: &ColorScheme) -> String {
+    let mut exported = String::new();
+    if !color_scheme.name.is_empty() {
+        exported.push_str(&format!("## name: {}\n\n", color_scheme.name));
+    }
+    for (key, color_opt) in [
+        ("foreground", color_scheme.foreground),
+        ("background", color_scheme.background),
+        ("cursor", color_scheme.cursor),
+    ] {
+        if let Some(color) = color_opt {
+            exported.push_str(&format!("{key} {}\n", hex(color)));
+        }
+    }
+    for (index, color) in indexed_iter(color_scheme) {
+        exported.push_str(&format!("color{index} {}\n", hex(color)));
+    }
+    exported
+}
+
+fn export_windows_terminal(color_scheme: &ColorScheme) -> Result<String, String> {
+    let mut scheme = serde_json::Map::new();
+    scheme.insert(
+        "name".to_string(),
+        serde_json::Value::String(color_scheme.name.clone()),
+    );
+    let mut insert = |key: String, color_opt: Option<HexColor>| {
+        if let Some(color) = color_opt {
+            scheme.insert(key, serde_json::Value::String(hex(color)));
+        }
+    };
+    insert("foreground".to_string(), color_scheme.foreground);
+    insert("background".to_string(), color_scheme.background);
+    insert("cursorColor".to_string(), color_scheme.cursor);
+    for (name, color_opt) in ansi_iter(&color_scheme.normal) {
+        // Windows Terminal calls magenta purple
+        let name = if name == "magenta" { "purple" } else { name };
+        insert(name.to_string(), color_opt);
+    }
+    for (name, color_opt) in ansi_iter(&color_scheme.bright) {
+        let name = if name == "magenta" { "Purple" } else { name };
+        let mut chars = name.chars();
+        let first = chars.next().unwrap_or_default().to_ascii_uppercase();
+        insert(format!("bright{first}{}", chars.as_str()), color_opt);
+    }
+    serde_json::to_string_pretty(&scheme).map_err(|err| err.to_string())
+}
+
+fn export_xresources(color_scheme: &ColorScheme) -> String {
+    let mut exported = String::new();
+    if !color_scheme.name.is_empty() {
+        exported.push_str(&format!("! {}\n\n", color_scheme.name));
+    }
+    for (key, color_opt) in [
+        ("foreground", color_scheme.foreground),
+        ("background", color_scheme.background),
+        ("cursorColor", color_scheme.cursor),
+    ] {
+        if let Some(color) = color_opt {
+            exported.push_str(&format!("*.{key}: {}\n", hex(color)));
+        }
+    }
+    for (index, color) in indexed_iter(color_scheme) {
+        exported.push_str(&format!("*.color{index}: {}\n", hex(color)));
+    }
+    exported
+}
+
+/// Import all color schemes from a file, detecting its format
+pub fn import(path: &Path) -> Result<Vec<ColorScheme>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|err| format!("Failed to open {path:?}: {err}"))?;
+    let format = ColorSchemeFormat::detect(path, &contents)
+        .ok_or_else(|| format!("Failed to parse {path:?}: unknown color scheme format"))?;
+    let mut color_schemes = format
+        .parse(&contents)
+        .map_err(|err| format!("Failed to parse {path:?}: {err}"))?;
+
+    // Formats without names use the file name
+    for color_scheme in &mut color_schemes {
+        if color_scheme.name.is_empty() {
+            if let Some(file_stem) = path.file_stem() {
+                color_scheme.name = file_stem.to_string_lossy().into_owned();
+            }
+        }
+    }
+
+    Ok(color_schemes)
+}
+
+// Parses #RGB, #RRGGBB, 0xRRGGBB, RRGGBB and X11 rgb:R/G/B colors
+fn parse_color(value: &str) -> Result<HexColor, String> {
+    let value = value.trim();
+    if let Some(rgb) = value.strip_prefix("rgb:") {
+        let mut components = [0u8; 3];
+        let mut parts = rgb.split('/');
+        for component in &mut components {
+            let part = parts
+                .next()
+                .filter(|part| !part.is_empty() && part.len() <= 4)
+                .ok_or_else(|| format!("invalid color {value:?}"))?;
+            let number =
+                u16::from_str_radix(part, 16).map_err(|_| format!("invalid color {value:?}"))?;
+            // Scale components of 1 to 4 hex digits to 8 bits
+            let max = (1u32 << (4 * part.len())) - 1;
+            *component = ((u32::from(number) * 255 + max / 2) / max) as u8;
+        }
+        return Ok(HexColor::rgb(components[0], components[1], components[2]));
+    }
+
+    let hex = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .unwrap_or(value);
+    HexColor::parse_rgb(&format!("#{hex}")).map_err(|err| format!("invalid color {value:?}: {err}"))
+}
+
+fn is_empty(color_scheme: &ColorScheme) -> bool {
+    color_scheme.foreground.is_none()
+        && color_scheme.background.is_none()
+        && color_scheme.normal.is_empty()
+        && color_scheme.bright.is_empty()
+}
+
+fn ansi_from_fn<F: FnMut(&str) -> Result<Option<HexColor>, String>>(
+    mut f: F,
+) -> Result<ColorSchemeAnsi, String> {
+    Ok(ColorSchemeAnsi {
+        black: f("black")?,
+        red: f("red")?,
+        green: f("green")?,
+        yellow: f("yellow")?,
+        blue: f("blue")?,
+        magenta: f("magenta")?,
+        cyan: f("cyan")?,
+        white: f("white")?,
+    })
+}
+
+// Set a color from its index in the 16 color palette
+fn set_indexed(color_scheme: &mut ColorScheme, index: usize, color: HexColor) {
+    if index < 8 {
+        color_scheme.normal.set(index, Some(color));
+    } else {
+        color_scheme.bright.set(index - 8, Some(color));
+    }
+}
+
+fn flatten_toml(prefix: String, value: &toml::Value, map: &mut BTreeMap<String, String>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_toml(key, value, map);
+            }
+        }
+        toml::Value::String(string) => {
+            map.insert(prefix, string.clone());
+        }
+        _ => {}
+    }
+}
+
+// Only the subset of YAML used by color schemes is supported: nested mappings of scalars
+fn parse_yaml(contents: &str) -> BTreeMap<String, String> {
+    let unquote = |value: &str| -> String {
+        let value = value.trim();
+        for quote in ['"', '\''] {
+            if let Some(rest) = value.strip_prefix(quote) {
+                return rest.split(quote).next().unwrap_or_default().to_string();
+            }
+        }
+        value
+            .split(" #")
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    };
+
+    let mut map = BTreeMap::new();
+    let mut parents: Vec<(usize, String)> = Vec::new();
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("---") {
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+
+        let indent = line.len() - trimmed.len();
+        while parents
+            .last()
+            .is_some_and(|(parent_indent, _)| *parent_indent >= indent)
+        {
+            parents.pop();
+        }
+
+        let key = unquote(key);
+        let value = unquote(value);
+        // Anchors on mappings are ignored
+        if value.is_empty() || value.starts_with('&') {
+            parents.push((indent, key));
+        } else {
+            let mut full_key = String::new();
+            for (_, parent) in &parents {
+                full_key.push_str(parent);
+                full_key.push('.');
+            }
+            full_key.push_str(&key);
+            map.insert(full_key, value);
+        }
+    }
+    map
+}
+
+fn alacritty(map: &BTreeMap<String, String>) -> Result<ColorScheme, String> {
+    let get = |key: &str| -> Result<Option<HexColor>, String> {
+        map.get(&format!("colors.{key}"))
+            // Cursor colors can refer to the cell colors instead
+            .filter(|value| !value.starts_with("Cell"))
+            .map(|value| parse_color(value))
+            .transpose()
+    };
+
+    let color_scheme = ColorScheme {
+        name: String::new(),
+        foreground: get("primary.foreground")?,
+        background: get("primary.background")?,
+        cursor: get("cursor.cursor")?,
+        bright_foreground: get("primary.bright_foreground")?,
+        dim_foreground: get("primary.dim_foreground")?,
+        normal: ansi_from_fn(|name| get(&format!("normal.{name}")))?,
+        bright: ansi_from_fn(|name| get(&format!("bright.{name}")))?,
+        dim: ansi_from_fn(|name| get(&format!("dim.{name}")))?,
+        selection_foreground: get("selection.text")?,
+        selection_background: get("selection.background")?,
+        search_match_foreground: get("search.matches.foreground")?,
+        search_match_background: get("search.matches.background")?,
+        focused_match_foreground: get("search.focused_match.foreground")?,
+        focused_match_background: get("search.focused_match.background")?,
+        hyperlink: None,
+    };
+    if is_empty(&color_scheme) {
+        return Err("no Alacritty colors found".to_string());
+    }
+    Ok(color_scheme)
+}
+
+// Mapping from https://github.com/chriskempson/base16/blob/main/styling.md, as used by
+// base16-shell
+fn base16(map: &BTreeMap<String, String>) -> Result<ColorScheme, String> {
+    let get = |base: &str| -> Result<Option<HexColor>, String> {
+        map.get(base)
+            .or_else(|| map.get(&format!("palette.{base}")))
+            .map(|value| parse_color(value))
+            .transpose()
+    };
+
+    let mut color_scheme = ColorScheme {
+        name: map
+            .get("scheme")
+            .or_else(|| map.get("name"))
+            .cloned()
+            .unwrap_or_default(),
+        foreground: get("base05")?,
+        background: get("base00")?,
+        cursor: get("base05")?,
+        ..Default::default()
+    };
+    for (index, base) in [
+        "base00", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base05", "base03",
+        "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base07",
+    ]
+    .iter()
+    .enumerate()
+    {
+        if let Some(color) = get(base)? {
+            set_indexed(&mut color_scheme, index, color);
+        }
+    }
+    if is_empty(&color_scheme) {
+        return Err("no base16 colors found".to_string());
+    }
+    Ok(color_scheme)
+}
+
+fn iterm2(contents: &str) -> Result<ColorScheme, String> {
+    // Property lists usually have a DOCTYPE declaration
+    let document = roxmltree::Document::parse_with_options(
+        contents,
+        roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        },
+    )
+    .map_err(|err| err.to_string())?;
+    let dict = document
+        .root_element()
+        .children()
+        .find(|node| node.has_tag_name("dict"))
+        .ok_or_else(|| "missing property list dictionary".to_string())?;
+
+    let mut color_scheme = ColorScheme::default();
+    let mut key_opt = None;
+    for node in dict.children().filter(|node| node.is_element()) {
+        if node.has_tag_name("key") {
+            key_opt = node.text();
+            continue;
+        }
+        let Some(key) = key_opt.take() else {
+            continue;
+        };
+        if !node.has_tag_name("dict") {
+            continue;
+        }
+
+        // Components are stored as reals from 0 to 1
+        let mut components = [0u8; 3];
+        let mut component_key_opt = None;
+        for component in node.children().filter(|node| node.is_element()) {
+            if component.has_tag_name("key") {
+                component_key_opt = component.text();
+                continue;
+            }
+            let index = match component_key_opt.take() {
+                Some("Red Component") => 0,
+                Some("Green Component") => 1,
+                Some("Blue Component") => 2,
+                _ => continue,
+            };
+            let text = component.text().unwrap_or_default().trim();
+            let value: f32 = text
+                .parse()
+                .map_err(|_| format!("invalid component {text:?} in {key:?}"))?;
+            components[index] = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+        let color = HexColor::rgb(components[0], components[1], components[2]);
+
+        match key {
+            "Foreground Color" => color_scheme.foreground = Some(color),
+            "Background Color" => color_scheme.background = Some(color),
+            "Cursor Color" => color_scheme.cursor = Some(color),
+            "Bold Color" => color_scheme.bright_foreground = Some(color),
+            _ => {
+                if let Some(index) = key
+                    .strip_prefix("Ansi ")
+                    .and_then(|key| key.strip_suffix(" Color"))
+                    .and_then(|index| index.parse::<usize>().ok())
+                {
+                    set_indexed(&mut color_scheme, index, color);
+                }
+            }
+        }
+    }
+    if is_empty(&color_scheme) {
+        return Err("no iTerm2 colors found".to_string());
+    }
+    Ok(color_scheme)
+}
+
+fn kitty(contents: &str) -> Result<ColorScheme, String> {
+    let mut color_scheme = ColorScheme::default();
+    for line in contents.lines() {
+        let line = line.trim();
+
+        // Themes from kitty-themes carry their name in a metadata comment
+        if let Some(name) = line.strip_prefix("## name:") {
+            color_scheme.name = name.trim().to_string();
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "foreground" => color_scheme.foreground = Some(parse_color(value)?),
+            "background" => color_scheme.background = Some(parse_color(value)?),
+            "cursor" if value != "none" => color_scheme.cursor = Some(parse_color(value)?),
+            _ => {
+                if let Some(index) = key
+                    .strip_prefix("color")
+                    .and_then(|index| index.parse::<usize>().ok())
+                {
+                    set_indexed(&mut color_scheme, index, parse_color(value)?);
+                }
+            }
+        }
+    }
+    if is_empty(&color_scheme) {
+        return Err("no kitty colors found".to_string());
+    }
+    Ok(color_scheme)
+}
+
+fn windows_terminal(contents: &str) -> Result<Vec<ColorScheme>, String> {
+    let value: serde_json::Value = serde_json::from_str(contents).map_err(|err| err.to_string())?;
+
+    // Settings files contain a list of schemes, but a single scheme can also be imported
+    let schemes = match value.get("schemes") {
+        Some(serde_json::Value::Array(schemes)) => schemes.iter().collect(),
+        _ => vec![&value],
+    };
+
+    let mut color_schemes = Vec::with_capacity(schemes.len());
+    for scheme in schemes {
+        let get = |key: &str| -> Result<Option<HexColor>, String> {
+            scheme
+                .get(key)
+                .and_then(|value| value.as_str())
+                .map(parse_color)
+                .transpose()
+        };
+        // Windows Terminal calls magenta purple
+        let ansi = |prefix: &str| {
+            ansi_from_fn(|name| {
+                let name = if name == "magenta" { "purple" } else { name };
+                if prefix.is_empty() {
+                    get(name)
+                } else {
+                    let mut chars = name.chars();
+                    let first = chars.next().unwrap_or_default().to_ascii_uppercase();
+                    get(&format!("{prefix}{first}{}", chars.as_str()))
+                }
+            })
+        };
+
+        let color_scheme = ColorScheme {
+            name: scheme
+                .get("name")
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            foreground: get("foreground")?,
+            background: get("background")?,
+            cursor: get("cursorColor")?,
+            normal: ansi("")?,
+            bright: ansi("bright")?,
+            ..Default::default()
+        };
+        if !is_empty(&color_scheme) {
+            color_schemes.push(color_scheme);
+        }
+    }
+    if color_schemes.is_empty() {
+        return Err("no Windows Terminal colors found".to_string());
+    }
+    Ok(color_schemes)
+}
+
+fn xresources(contents: &str) -> Result<ColorScheme, String> {
+    let mut defines = BTreeMap::new();
+    let mut color_scheme = ColorScheme::default();
+    for line in contents.lines() {
+        let line = line.trim();
+
+        // Some schemes use preprocessor defines for their palette
+        if let Some(define) = line.strip_prefix("#define") {
+            let mut parts = define.split_whitespace();
+            if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                defines.insert(name.to_string(), value.to_string());
+            }
+            continue;
+        }
+        if line.is_empty() || line.starts_with('!') || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((resource, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Only the last component of a resource name like URxvt*color0 or *.background matters
+        let key = resource
+            .rsplit(['.', '*'])
+            .next()
+            .unwrap_or_default()
+            .trim();
+        let value = value.trim();
+        let value = defines.get(value).map_or(value, |value| value.as_str());
+        match key {
+            "foreground" => color_scheme.foreground = Some(parse_color(value)?),
+            "background" => color_scheme.background = Some(parse_color(value)?),
+            "cursorColor" => color_scheme.cursor = Some(parse_color(value)?),
+            _ => {
+                if let Some(index) = key
+                    .strip_prefix("color")
+                    .and_then(|index| index.parse::<usize>().ok())
+                {
+                    set_indexed(&mut color_scheme, index, parse_color(value)?);
+                }
+            }
+        }
+    }
+    if is_empty(&color_scheme) {
+        return Err("no X resources colors found".to_string());
+    }
+    Ok(color_scheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(value: u32) -> Option<HexColor> {
+        Some(HexColor::rgb(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ))
+    }
+
+    // Only keep the parts of a color scheme that survive a round trip through a format
+    fn supported(format: ColorSchemeFormat, color_scheme: &ColorScheme) -> ColorScheme {
+        let mut supported = color_scheme.clone();
+        if matches!(format, ColorSchemeFormat::Ron) {
+            return supported;
+        }
+        if !matches!(
+            format,
+            ColorSchemeFormat::Kitty | ColorSchemeFormat::WindowsTerminal
+        ) {
+            supported.name.clear();
+        }
+        // Hyperlink colors are only stored by COSMIC Terminal
+        supported.hyperlink = None;
+        if !matches!(
+            format,
+            ColorSchemeFormat::Alacritty | ColorSchemeFormat::AlacrittyYaml
+        ) {
+            supported.selection_foreground = None;
+            supported.selection_background = None;
+            supported.search_match_foreground = None;
+            supported.search_match_background = None;
+            supported.focused_match_foreground = None;
+            supported.focused_match_background = None;
+            supported.dim_foreground = None;
+            supported.dim = ColorSchemeAnsi::default();
+            if !matches!(format, ColorSchemeFormat::ITerm2) {
+                supported.bright_foreground = None;
+            }
+        }
+        supported
+    }
+
+    fn parse_one(format: ColorSchemeFormat, contents: &str) -> ColorScheme {
+        let mut color_schemes = format.parse(contents).unwrap();
+        assert_eq!(color_schemes.len(), 1);
+        color_schemes.remove(0)
+    }
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(parse_color("#1a2B3c").ok(), rgb(0x1a2b3c));
+        assert_eq!(parse_color("0x1a2b3c").ok(), rgb(0x1a2b3c));
+        assert_eq!(parse_color("1a2b3c").ok(), rgb(0x1a2b3c));
+        assert_eq!(parse_color(" #fff ").ok(), rgb(0xffffff));
+        assert_eq!(parse_color("rgb:ff/80/00").ok(), rgb(0xff8000));
+        assert_eq!(parse_color("rgb:ffff/8080/0").ok(), rgb(0xff8000));
+        assert_eq!(parse_color("rgb:f/8/0").ok(), rgb(0xff8800));
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("rgb:ff/80").is_err());
+        assert!(parse_color("red").is_err());
+    }
+
+    #[test]
+    fn parse_alacritty() {
+        let color_scheme = parse_one(
+            ColorSchemeFormat::Alacritty,
+            r##"
+# Colors (Example)
+[colors.primary]
+foreground = '#d8d8d8' # comment after a value
+background = "0x181818"
+dim_foreground = "#828482"
+
+[colors.cursor]
+text = "CellBackground"
+cursor = "CellForeground"
+
+[colors.normal]
+black = "#181818"
+red = "#ac4242"
+green = "#90a959"
+yellow = "#f4bf75"
+blue = "#6a9fb5"
+magenta = "#aa759f"
+cyan = "#75b5aa"
+white = "#d8d8d8"
+
+[colors.bright]
+black = "#6b6b6b"
+white = "#f8f8f8"
+
+[colors.selection]
+text = "#000000"
+background = "#fffacd"
+
+[colors.search.matches]
+foreground = "#000000"
+background = "#ac4242"
+
+[colors.footer_bar]
+background = "#404040"
+
+[font]
+size = 12.0
+"##,
+        );
+        assert_eq!(
+            color_scheme,
+            ColorScheme {
+                foreground: rgb(0xd8d8d8),
+                background: rgb(0x181818),
+                dim_foreground: rgb(0x828482),
+                normal: ColorSchemeAnsi {
+                    black: rgb(0x181818),
+                    red: rgb(0xac4242),
+                    green: rgb(0x90a959),
+                    yellow: rgb(0xf4bf75),
+                    blue: rgb(0x6a9fb5),
+                    magenta: rgb(0xaa759f),
+                    cyan: rgb(0x75b5aa),
+                    white: rgb(0xd8d8d8),
+                },
+                bright: ColorSchemeAnsi {
+                    black: rgb(0x6b6b6b),
+                    white: rgb(0xf8f8f8),
+                    ..Default::default()
+                },
+                selection_foreground: rgb(0x000000),
+                selection_background: rgb(0xfffacd),
+                search_match_foreground: rgb(0x000000),
+                search_match_background: rgb(0xac4242),
+                ..Default::default()
+            }
+        );
+
+        assert!(ColorSchemeFormat::Alacritty
+            .parse("[font]\nsize = 12.0\n")
+            .is_err());
+        assert!(ColorSchemeFormat::Alacritty
+            .parse("[colors.primary]\nforeground = \"#zzzzzz\"\n")
+            .is_err());
+        assert!(ColorSchemeFormat::Alacritty.parse("[colors").is_err());
+    }
+
+    #[test]
+    fn parse_alacritty_yaml() {
+        let color_scheme = parse_one(
+            ColorSchemeFormat::AlacrittyYaml,
+            r##"
+# Base16 Default Dark
+---
+schemes:
+  dark: &dark
+    primary:
+      background: '0x181818'
+      foreground: "#d8d8d8" # comment after a value
+
+colors:
+  # Default colors
+  primary:
+    background: '0x181818'
+    foreground: '0xd8d8d8'
+  cursor:
+    text: CellBackground
+    cursor: CellForeground
+  normal:
+    black:   '#181818'
+    red:     0xac4242
+  bright:
+    white: "#f8f8f8"
+  vi_mode_cursor:
+    cursor: '0xff0000'
+font:
+  size: 12
+"##,
+        );
+        assert_eq!(
+            color_scheme,
+            ColorScheme {
+                foreground: rgb(0xd8d8d8),
+                background: rgb(0x181818),
+                normal: ColorSchemeAnsi {
+                    black: rgb(0x181818),
+                    red: rgb(0xac4242),
+                    ..Default::default()
+                },
+                bright: ColorSchemeAnsi {
+                    white: rgb(0xf8f8f8),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+
+        assert!(ColorSchemeFormat::AlacrittyYaml
+            .parse("font:\n  size: 12\n")
+            .is_err());
+    }
+
+    #[test]
+    fn parse_base16() {
+        let color_scheme = parse_one(
+            ColorSchemeFormat::Base16,
+            r#"
+scheme: "Default Dark"
+author: "Chris Kempson (http://chriskempson.com)"
+# Colors without a leading #
+base00: "181818"
+base01: "282828"
+base02: "383838"
+base03: "585858"
+base04: "b8b8b8"
+base05: "d8d8d8"
+base06: "e8e8e8"
+base07: "f8f8f8"
+base08: "ab4642"
+base09: "dc9656"
+base0A: "f7ca88"
+base0B: "a1b56c"
+base0C: "86c1b9"
+base0D: "7cafc2"
+base0E: "ba8baf"
+base0F: "a16946"
+"#,
+        );
+        assert_eq!(
+            color_scheme,
+            ColorScheme {
+                name: "Default Dark".to_string(),
+                foreground: rgb(0xd8d8d8),
+                background: rgb(0x181818),
+                cursor: rgb(0xd8d8d8),
+                normal: ColorSchemeAnsi {
+                    black: rgb(0x181818),
+                    red: rgb(0xab4642),
+                    green: rgb(0xa1b56c),
+                    yellow: rgb(0xf7ca88),
+                    blue: rgb(0x7cafc2),
+                    magenta: rgb(0xba8baf),
+                    cyan: rgb(0x86c1b9),
+                    white: rgb(0xd8d8d8),
+                },
+                bright: ColorSchemeAnsi {
+                    black: rgb(0x585858),
+                    red: rgb(0xab4642),
+                    green: rgb(0xa1b56c),
+                    yellow: rgb(0xf7ca88),
+                    blue: rgb(0x7cafc2),
+                    magenta: rgb(0xba8baf),
+                    cyan: rgb(0x86c1b9),
+                    white: rgb(0xf8f8f8),
+                },
+                ..Default::default()
+            }
+        );
+
+        // The tinted-theming format nests the colors in a palette
+        let color_scheme = parse_one(
+            ColorSchemeFormat::Base16,
+            "system: \"base16\"\nname: 'Palette'\nvariant: \"dark\"\npalette:\n  base00: \"#000000\"\n  base05: \"#ffffff\"\n",
+        );
+        assert_eq!(color_scheme.name, "Palette");
+        assert_eq!(color_scheme.background, rgb(0x000000));
+        assert_eq!(color_scheme.foreground, rgb(0xffffff));
+
+        assert!(ColorSchemeFormat::Base16
+            .parse("scheme: \"Empty\"\n")
+            .is_err());
+    }
+
+    #[test]
+    fn parse_iterm2() {
+        let color_scheme = parse_one(
+            ColorSchemeFormat::ITerm2,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<!-- Red -->
+	<key>Ansi 1 Color</key>
+	<dict>
+		<key>Alpha Component</key>
+		<real>1</real>
+		<key>Blue Component</key>
+		<real>0.0</real>
+		<key>Color Space</key>
+		<string>sRGB</string>
+		<key>Green Component</key>
+		<real>0.5</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+	<key>Ansi 15 Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>1</real>
+		<key>Green Component</key>
+		<real>1</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+	<key>Background Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>0.09411764705882353</real>
+		<key>Green Component</key>
+		<real>0.09411764705882353</real>
+		<key>Red Component</key>
+		<real>0.09411764705882353</real>
+	</dict>
+	<key>Selection Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>1</real>
+		<key>Green Component</key>
+		<real>1</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+	<key>Bold Color</key>
+	<dict>
+		<key>Blue Component</key>
+		<real>1.5</real>
+		<key>Green Component</key>
+		<real>1</real>
+		<key>Red Component</key>
+		<real>1</real>
+	</dict>
+</dict>
+</plist>
+"#,
+        );
+        assert_eq!(
+            color_scheme,
+            ColorScheme {
+                background: rgb(0x181818),
+                bright_foreground: rgb(0xffffff),
+                normal: ColorSchemeAnsi {
+                    red: rgb(0xff8000),
+                    ..Default::default()
+                },
+                bright: ColorSchemeAnsi {
+                    white: rgb(0xffffff),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+
+        assert!(ColorSchemeFormat::ITerm2
+            .parse("<plist version=\"1.0\"><dict></dict></plist>")
+            .is_err());
+        assert!(ColorSchemeFormat::ITerm2.parse("<plist>").is_err());
+    }
+
+    #[test]
+    fn parse_kitty() {
+        let color_scheme = parse_one(
+            ColorSchemeFormat::Kitty,
+            "
+# vim:ft=kitty
+## name: Example
+## author: Someone
+
+foreground   #d8d8d8
+background   0x181818
+cursor       none
+selection_background #fffacd
+font_size 12.0
+
+# black
+color0 #181818
+color9\t#ac4242
+color16 #dc9656
+",
+        );
+        assert_eq!(
+            color_scheme,
+            ColorScheme {
+                name: "Example".to_string(),
+                foreground: rgb(0xd8d8d8),
+                background: rgb(0x181818),
+                normal: ColorSchemeAnsi {
+                    black: rgb(0x181818),
+                    ..Default::default()
+                },
+                bright: ColorSchemeAnsi {
+                    red: rgb(0xac4242),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+
+        assert!(ColorSchemeFormat::Kitty.parse("font_size 12.0\n").is_err());
+        assert!(ColorSchemeFormat::Kitty.parse("color0 #zzzzzz\n").is_err());
+    }
+
+    #[test]
+    fn parse_windows_terminal() {
+        // A single scheme
+        let color_scheme = parse_one(
+            ColorSchemeFormat::WindowsTerminal,
+            r##"{
+    "name": "Campbell",
+    "cursorColor": "#FFFFFF",
+    "selectionBackground": "#FFFFFF",
+    "background": "#0C0C0C",
+    "foreground": "#CCCCCC",
+    "black": "#0C0C0C",
+    "purple": "#881798",
+    "brightPurple": "#B4009E",
+    "brightWhite": "#F2F2F2"
+}"##,
+        );
+        assert_eq!(
+            color_scheme,
+            ColorScheme {
+                name: "Campbell".to_string(),
+                foreground: rgb(0xcccccc),
+                background: rgb(0x0c0c0c),
+                cursor: rgb(0xffffff),
+                normal: ColorSchemeAnsi {
+                    black: rgb(0x0c0c0c),
+                    magenta: rgb(0x881798),
+                    ..Default::default()
+                },
+                bright: ColorSchemeAnsi {
+                    magenta: rgb(0xb4009e),
+                    white: rgb(0xf2f2f2),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+
+        // A settings file, schemes without colors are skipped
+        let color_schemes = ColorSchemeFormat::WindowsTerminal
+            .parse(
+                r##"{
+    "defaultProfile": "{00000000-0000-0000-0000-000000000000}",
+    "profiles": { "list": [] },
+    "schemes": [
+        { "name": "One", "background": "#000000" },
+        { "name": "Empty" },
+        { "name": "Two", "foreground": "#FFFFFF" }
+    ]
+}"##,
+            )
+            .unwrap();
+        assert_eq!(
+            color_schemes
+                .iter()
+                .map(|color_scheme| color_scheme.name.as_str())
+                .collect::<Vec<_>>(),
+            ["One", "Two"]
+        );
+
+        assert!(ColorSchemeFormat::WindowsTerminal
+            .parse(r#"{ "schemes": [] }"#)
+            .is_err());
+        assert!(ColorSchemeFormat::WindowsTerminal.parse("{").is_err());
+    }
+
+    #[test]
+    fn parse_xresources() {
+        let color_scheme = parse_one(
+            ColorSchemeFormat::Xresources,
+            "
+! Example scheme
+#define S_base00 #181818
+#define S_red    #ac4242
+
+*.foreground:   #d8d8d8
+URxvt*background: S_base00
+*cursorColor: rgb:ff/ff/ff
+*.color0:       S_base00
+XTerm.vt100.color1: S_red
+*color15:       0xf8f8f8
+URxvt.font: xft:Monospace:size=12
+",
+        );
+        assert_eq!(
+            color_scheme,
+            ColorScheme {
+                foreground: rgb(0xd8d8d8),
+                background: rgb(0x181818),
+                cursor: rgb(0xffffff),
+                normal: ColorSchemeAnsi {
+                    black: rgb(0x181818),
+                    red: rgb(0xac4242),
+                    ..Default::default()
+                },
+                bright: ColorSchemeAnsi {
+                    white: rgb(0xf8f8f8),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        );
+
+        assert!(ColorSchemeFormat::Xresources
+            .parse("URxvt.font: xft:Monospace\n")
+            .is_err());
+        assert!(ColorSchemeFormat::Xresources
+            .parse("*.color0: S_missing\n")
+            .is_err());
+    }
+
+    #[test]
+    fn export_round_trip() {
+        // Every color is different so that swapped colors are noticed
+        let mut next = 0x102030;
+        let mut color = || {
+            next += 0x050301;
+            rgb(next)
+        };
+        let mut ansi = || ColorSchemeAnsi {
+            black: color(),
+            red: color(),
+            green: color(),
+            yellow: color(),
+            blue: color(),
+            magenta: color(),
+            cyan: color(),
+            white: color(),
+        };
+        let normal = ansi();
+        let bright = ansi();
+        let dim = ansi();
+        let color_scheme = ColorScheme {
+            name: "Round Trip".to_string(),
+            foreground: color(),
+            background: color(),
+            cursor: color(),
+            bright_foreground: color(),
+            dim_foreground: color(),
+            normal,
+            bright,
+            dim,
+            selection_foreground: color(),
+            selection_background: color(),
+            search_match_foreground: color(),
+            search_match_background: color(),
+            focused_match_foreground: color(),
+            focused_match_background: color(),
+            hyperlink: color(),
+        };
+
+        for format in ColorSchemeFormat::EXPORT {
+            let exported = format.export(&color_scheme).unwrap();
+            assert_eq!(
+                format.parse(&exported),
+                Ok(vec![supported(format, &color_scheme)]),
+                "{}",
+                format.name()
+            );
+        }
+
+        for format in [ColorSchemeFormat::AlacrittyYaml, ColorSchemeFormat::Base16] {
+            assert!(format.export(&color_scheme).is_err());
+        }
+    }
+
+    #[test]
+    fn detect() {
+        let detect =
+            |path: &str, contents: &str| ColorSchemeFormat::detect(Path::new(path), contents);
+
+        // Extensions
+        assert_eq!(detect("a.ron", ""), Some(ColorSchemeFormat::Ron));
+        assert_eq!(detect("a.itermcolors", ""), Some(ColorSchemeFormat::ITerm2));
+        assert_eq!(detect("a.TOML", ""), Some(ColorSchemeFormat::Alacritty));
+        assert_eq!(
+            detect("a.yml", "colors:\n  primary:\n"),
+            Some(ColorSchemeFormat::AlacrittyYaml)
+        );
+        assert_eq!(
+            detect("a.yaml", "base00: \"181818\"\n"),
+            Some(ColorSchemeFormat::Base16)
+        );
+        assert_eq!(
+            detect("a.yaml", "palette:\n  base00: \"181818\"\n"),
+            Some(ColorSchemeFormat::Base16)
+        );
+        assert_eq!(detect("a.conf", ""), Some(ColorSchemeFormat::Kitty));
+        assert_eq!(
+            detect("a.json", ""),
+            Some(ColorSchemeFormat::WindowsTerminal)
+        );
+        assert_eq!(
+            detect("a.Xresources", ""),
+            Some(ColorSchemeFormat::Xresources)
+        );
+        assert_eq!(
+            detect(".Xresources", ""),
+            Some(ColorSchemeFormat::Xresources)
+        );
+        assert_eq!(
+            detect(".Xdefaults-hostname", ""),
+            Some(ColorSchemeFormat::Xresources)
+        );
+
+        // Contents
+        assert_eq!(
+            detect("a", "  <?xml version=\"1.0\"?>"),
+            Some(ColorSchemeFormat::ITerm2)
+        );
+        assert_eq!(
+            detect("a", "{ \"name\": \"x\" }"),
+            Some(ColorSchemeFormat::WindowsTerminal)
+        );
+        assert_eq!(detect("a", "(name: \"x\")"), Some(ColorSchemeFormat::Ron));
+        assert_eq!(
+            detect("a", "[colors.primary]\n"),
+            Some(ColorSchemeFormat::Alacritty)
+        );
+        assert_eq!(
+            detect("a", "base00: \"181818\"\n"),
+            Some(ColorSchemeFormat::Base16)
+        );
+        assert_eq!(
+            detect("a", "*.color0: #000000\n"),
+            Some(ColorSchemeFormat::Xresources)
+        );
+        assert_eq!(
+            detect("a", "# theme\ncolor0 #000000\n"),
+            Some(ColorSchemeFormat::Kitty)
+        );
+        assert_eq!(
+            detect("a", "colors:\n  primary:\n"),
+            Some(ColorSchemeFormat::AlacrittyYaml)
+        );
+        assert_eq!(detect("a", "hello"), None);
+    }
+}
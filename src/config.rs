@@ -8,14 +8,19 @@ use cosmic_text::{Metrics, Stretch, Weight};
 use hex_color::HexColor;
 use serde::{Deserialize, Serialize};
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
-use crate::{fl, localize::LANGUAGE_SORTER};
+use crate::{fl, localize::LANGUAGE_SORTER, shell_integration::ShellLocation};
 
 pub const CONFIG_VERSION: u64 = 1;
 pub const COSMIC_THEME_DARK: &str = "COSMIC Dark";
 pub const COSMIC_THEME_LIGHT: &str = "COSMIC Light";
+pub const COSMIC_THEME_DESKTOP: &str = "Auto (desktop)";
+// Scrollback is kept in memory, so it is limited until it can be stored on disk
+pub const MAX_SCROLLING_HISTORY: u32 = 1_000_000;
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum AppTheme {
@@ -40,6 +45,26 @@ impl AppTheme {
             Self::System => theme::system_preference(),
         }
     }
+
+    pub fn color_scheme_kind(&self) -> ColorSchemeKind {
+        if self.theme().theme_type.is_dark() {
+            ColorSchemeKind::Dark
+        } else {
+            ColorSchemeKind::Light
+        }
+    }
+}
+
+/// How a background image fills the terminal
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BackgroundImageMode {
+    /// Scale to cover the terminal, cropping the image
+    #[default]
+    Scaled,
+    /// Repeat the image at its original size
+    Tiled,
+    /// Center the image at its original size
+    Centered,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -127,6 +152,36 @@ pub struct ColorSchemeAnsi {
 }
 
 impl ColorSchemeAnsi {
+    /// Get a color by its index, from black (0) to white (7)
+    pub fn get(&self, index: usize) -> Option<HexColor> {
+        match index {
+            0 => self.black,
+            1 => self.red,
+            2 => self.green,
+            3 => self.yellow,
+            4 => self.blue,
+            5 => self.magenta,
+            6 => self.cyan,
+            7 => self.white,
+            _ => None,
+        }
+    }
+
+    /// Set a color by its index, from black (0) to white (7)
+    pub fn set(&mut self, index: usize, color: Option<HexColor>) {
+        match index {
+            0 => self.black = color,
+            1 => self.red = color,
+            2 => self.green = color,
+            3 => self.yellow = color,
+            4 => self.blue = color,
+            5 => self.magenta = color,
+            6 => self.cyan = color,
+            7 => self.white = color,
+            _ => {}
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.black.is_none()
             && self.red.is_none()
@@ -179,12 +234,88 @@ pub struct ColorScheme {
     pub bright: ColorSchemeAnsi,
     #[serde(skip_serializing_if = "ColorSchemeAnsi::is_empty")]
     pub dim: ColorSchemeAnsi,
+    #[serde(
+        deserialize_with = "de_color_opt",
+        serialize_with = "ser_color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub selection_foreground: Option<HexColor>,
+    #[serde(
+        deserialize_with = "de_color_opt",
+        serialize_with = "ser_color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub selection_background: Option<HexColor>,
+    #[serde(
+        deserialize_with = "de_color_opt",
+        serialize_with = "ser_color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub search_match_foreground: Option<HexColor>,
+    #[serde(
+        deserialize_with = "de_color_opt",
+        serialize_with = "ser_color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub search_match_background: Option<HexColor>,
+    #[serde(
+        deserialize_with = "de_color_opt",
+        serialize_with = "ser_color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub focused_match_foreground: Option<HexColor>,
+    #[serde(
+        deserialize_with = "de_color_opt",
+        serialize_with = "ser_color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub focused_match_background: Option<HexColor>,
+    #[serde(
+        deserialize_with = "de_color_opt",
+        serialize_with = "ser_color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hyperlink: Option<HexColor>,
+}
+
+/// How a profile chooses between its dark and light color schemes
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ColorSchemeMode {
+    /// Follow the application theme
+    #[default]
+    App,
+    /// Follow the desktop preference, even if the application theme is fixed
+    Desktop,
+    /// Always use the color scheme of this kind
+    Fixed(ColorSchemeKind),
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 #[serde(transparent)]
 pub struct ProfileId(pub u64);
 
+/// When to close a tab after its process exits
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CloseOnExit {
+    /// Close the tab, whatever the exit status
+    // Default of profiles without the option, as they used to default to not holding the tab
+    #[default]
+    #[serde(alias = "false")]
+    Always,
+    /// Keep the tab open, showing the exit status
+    #[serde(alias = "true")]
+    Never,
+    /// Close the tab if the process exited successfully, otherwise keep it open
+    Clean,
+}
+
+/// Environment variable of a profile, a value of `None` unsets the variable
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ProfileEnv {
+    pub name: String,
+    pub value: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Profile {
     pub name: String,
@@ -195,11 +326,32 @@ pub struct Profile {
     #[serde(default)]
     pub syntax_theme_light: String,
     #[serde(default)]
+    pub color_scheme_mode: ColorSchemeMode,
+    #[serde(default)]
     pub tab_title: String,
     #[serde(default)]
     pub working_directory: String,
     #[serde(default)]
-    pub hold: bool,
+    pub login_shell: bool,
+    #[serde(default)]
+    pub initial_input: String,
+    // Profiles used to have a hold option instead, RON reads its value like a variant name
+    #[serde(default, alias = "hold")]
+    pub close_on_exit: CloseOnExit,
+    #[serde(default)]
+    pub opacity: Option<u8>,
+    #[serde(default)]
+    pub env: Vec<ProfileEnv>,
+    #[serde(default)]
+    pub font_name: Option<String>,
+    #[serde(default)]
+    pub font_size: Option<u16>,
+    #[serde(default)]
+    pub line_height_mul_100: Option<u16>,
+    #[serde(default)]
+    pub padding: Option<u16>,
+    #[serde(default)]
+    pub scrolling_history: Option<u32>,
 }
 
 impl Default for Profile {
@@ -209,16 +361,173 @@ impl Default for Profile {
             command: String::new(),
             syntax_theme_dark: COSMIC_THEME_DARK.to_string(),
             syntax_theme_light: COSMIC_THEME_LIGHT.to_string(),
+            color_scheme_mode: ColorSchemeMode::App,
             tab_title: String::new(),
             working_directory: String::new(),
-            hold: true,
+            login_shell: false,
+            initial_input: String::new(),
+            close_on_exit: CloseOnExit::Never,
+            opacity: None,
+            env: Vec::new(),
+            font_name: None,
+            font_size: None,
+            line_height_mul_100: None,
+            padding: None,
+            scrolling_history: None,
         }
     }
 }
 
+impl Profile {
+    /// Get the environment variables to set, with `$VAR` and `${VAR}` expanded, and the ones to
+    /// unset. Variables can refer to ones set earlier in the profile.
+    pub fn environment(&self) -> (HashMap<String, String>, Vec<String>) {
+        self.environment_with(|name| env::var(name).ok())
+    }
+
+    // Same as environment, looking up variables not set by the profile with var
+    fn environment_with<F: Fn(&str) -> Option<String>>(
+        &self,
+        var: F,
+    ) -> (HashMap<String, String>, Vec<String>) {
+        let mut set = HashMap::new();
+        let mut unset = Vec::new();
+        for profile_env in &self.env {
+            let name = profile_env.name.trim();
+            if name.is_empty() {
+                continue;
+            }
+            match &profile_env.value {
+                Some(value) => {
+                    let value = expand_env(value, &set, &unset, &var);
+                    unset.retain(|unset_name| unset_name != name);
+                    set.insert(name.to_string(), value);
+                }
+                None => {
+                    set.remove(name);
+                    if !unset.iter().any(|unset_name| unset_name == name) {
+                        unset.push(name.to_string());
+                    }
+                }
+            }
+        }
+        (set, unset)
+    }
+}
+
+// Expand `$VAR` and `${VAR}`, `$$` is a literal dollar sign
+fn expand_env<F: Fn(&str) -> Option<String>>(
+    value: &str,
+    set: &HashMap<String, String>,
+    unset: &[String],
+    var: &F,
+) -> String {
+    let lookup = |name: &str| -> String {
+        if let Some(value) = set.get(name) {
+            value.clone()
+        } else if unset.iter().any(|unset_name| unset_name == name) {
+            String::new()
+        } else {
+            var(name).unwrap_or_default()
+        }
+    };
+
+    let mut expanded = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('$') => {
+                chars.next();
+                expanded.push('$');
+            }
+            Some('{') => {
+                chars.next();
+                let mut name = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    name.push(c);
+                }
+                if closed {
+                    expanded.push_str(&lookup(&name));
+                } else {
+                    // Keep unterminated references as they are
+                    expanded.push_str("${");
+                    expanded.push_str(&name);
+                }
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                expanded.push_str(&lookup(&name));
+            }
+            _ => expanded.push('$'),
+        }
+    }
+    expanded
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct ProfileRuleId(pub u64);
+
+/// Switch to a profile while the shell location matches, empty fields match anything
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ProfileRule {
+    pub profile_id: ProfileId,
+    #[serde(default)]
+    pub host: String,
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub directory: String,
+}
+
+impl ProfileRule {
+    pub fn matches(&self, location: &ShellLocation) -> bool {
+        let host = self.host.trim();
+        let user = self.user.trim();
+        let directory = self.directory.trim();
+        // Rules without conditions would always match
+        if host.is_empty() && user.is_empty() && directory.is_empty() {
+            return false;
+        }
+
+        let matches_field = |pattern: &str, value: &Option<String>| {
+            pattern.is_empty()
+                || value
+                    .as_deref()
+                    .is_some_and(|value| value.eq_ignore_ascii_case(pattern))
+        };
+        // The directory also matches its subdirectories
+        let matches_directory = directory.is_empty()
+            || location.directory.as_deref().is_some_and(|value| {
+                value
+                    .strip_prefix(directory.trim_end_matches('/'))
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            });
+
+        matches_field(host, &location.host)
+            && matches_field(user, &location.user)
+            && matches_directory
+    }
+}
+
 #[derive(Clone, CosmicConfigEntry, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Config {
     pub app_theme: AppTheme,
+    pub background_image: Option<PathBuf>,
+    pub background_image_dim: u8,
+    pub background_image_mode: BackgroundImageMode,
     pub color_schemes_dark: BTreeMap<ColorSchemeId, ColorScheme>,
     pub color_schemes_light: BTreeMap<ColorSchemeId, ColorScheme>,
     pub font_name: String,
@@ -228,8 +537,13 @@ pub struct Config {
     pub bold_font_weight: u16,
     pub font_stretch: u16,
     pub font_size_zoom_step_mul_100: u16,
+    pub minimum_contrast_mul_10: Option<u8>,
     pub opacity: u8,
     pub profiles: BTreeMap<ProfileId, Profile>,
+    // Rules are checked in order of their IDs
+    pub profile_rules: BTreeMap<ProfileRuleId, ProfileRule>,
+    // Number of lines kept in the scrollback, capped at MAX_SCROLLING_HISTORY
+    pub scrolling_history: u32,
     pub show_headerbar: bool,
     pub use_bright_bold: bool,
     pub syntax_theme_dark: String,
@@ -242,6 +556,9 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             app_theme: AppTheme::System,
+            background_image: None,
+            background_image_dim: 50,
+            background_image_mode: BackgroundImageMode::Scaled,
             bold_font_weight: Weight::BOLD.0,
             color_schemes_dark: BTreeMap::new(),
             color_schemes_light: BTreeMap::new(),
@@ -252,8 +569,12 @@ impl Default for Config {
             font_size_zoom_step_mul_100: 100,
             font_stretch: Stretch::Normal.to_number(),
             font_weight: Weight::NORMAL.0,
+            minimum_contrast_mul_10: None,
             opacity: 100,
             profiles: BTreeMap::new(),
+            profile_rules: BTreeMap::new(),
+            // Same as the default of alacritty
+            scrolling_history: 10_000,
             show_headerbar: true,
             syntax_theme_dark: COSMIC_THEME_DARK.to_string(),
             syntax_theme_light: COSMIC_THEME_LIGHT.to_string(),
@@ -285,11 +606,7 @@ impl Config {
     }
 
     pub fn color_scheme_kind(&self) -> ColorSchemeKind {
-        if self.app_theme.theme().theme_type.is_dark() {
-            ColorSchemeKind::Dark
-        } else {
-            ColorSchemeKind::Light
-        }
+        self.app_theme.color_scheme_kind()
     }
 
     // Get a sorted and adjusted for duplicates list of color scheme names and ids
@@ -315,22 +632,80 @@ impl Config {
         color_scheme_names
     }
 
-    fn font_size_adjusted(&self, zoom_adj: i8) -> f32 {
-        let font_size = f32::from(self.font_size).max(1.0);
+    fn profile(&self, profile_id_opt: Option<ProfileId>) -> Option<&Profile> {
+        profile_id_opt.and_then(|profile_id| self.profiles.get(&profile_id))
+    }
+
+    /// Get the profile of the first rule matching the shell location
+    pub fn matching_profile(&self, location: &ShellLocation) -> Option<ProfileId> {
+        self.profile_rules
+            .values()
+            .find(|rule| rule.matches(location) && self.profiles.contains_key(&rule.profile_id))
+            .map(|rule| rule.profile_id)
+    }
+
+    // Get font family of a profile, None if it uses the default monospace font
+    pub fn font_name(&self, profile_id_opt: Option<ProfileId>) -> Option<&str> {
+        self.profile(profile_id_opt)
+            .and_then(|profile| profile.font_name.as_deref())
+            .filter(|font_name| *font_name != self.font_name)
+    }
+
+    fn font_size_adjusted(&self, profile_id_opt: Option<ProfileId>, zoom_adj: i8) -> f32 {
+        let font_size = self
+            .profile(profile_id_opt)
+            .and_then(|profile| profile.font_size)
+            .unwrap_or(self.font_size);
+        let font_size = f32::from(font_size).max(1.0);
         let adj = f32::from(zoom_adj);
         let adj_step = f32::from(self.font_size_zoom_step_mul_100) / 100.0;
         (font_size + adj * adj_step).max(1.0)
     }
 
-    // Calculate metrics from font size
-    pub fn metrics(&self, zoom_adj: i8) -> Metrics {
-        let font_size = self.font_size_adjusted(zoom_adj);
-        let line_height = (font_size * 1.4).ceil();
-        Metrics::new(font_size, line_height)
+    // Calculate metrics from font size and line height of a profile
+    pub fn metrics(&self, profile_id_opt: Option<ProfileId>, zoom_adj: i8) -> Metrics {
+        let font_size = self.font_size_adjusted(profile_id_opt, zoom_adj);
+        let line_height_mul_100 = self
+            .profile(profile_id_opt)
+            .and_then(|profile| profile.line_height_mul_100)
+            .unwrap_or(140);
+        let line_height = (font_size * f32::from(line_height_mul_100) / 100.0).ceil();
+        Metrics::new(font_size, line_height.max(1.0))
     }
 
-    pub fn opacity_ratio(&self) -> f32 {
-        f32::from(self.opacity) / 100.0
+    // Get padding around the terminal of a profile, if it has custom padding
+    pub fn padding(&self, profile_id_opt: Option<ProfileId>) -> Option<u16> {
+        self.profile(profile_id_opt)
+            .and_then(|profile| profile.padding)
+    }
+
+    // Get number of scrollback lines of a profile, falling back to the default one
+    pub fn scrolling_history(&self, profile_id_opt: Option<ProfileId>) -> u32 {
+        self.profile(profile_id_opt)
+            .and_then(|profile| profile.scrolling_history)
+            .unwrap_or(self.scrolling_history)
+            .min(MAX_SCROLLING_HISTORY)
+    }
+
+    // Minimum WCAG contrast ratio of text against its background, if enforced
+    pub fn minimum_contrast(&self) -> Option<f64> {
+        self.minimum_contrast_mul_10
+            .map(|minimum_contrast| f64::from(minimum_contrast) / 10.0)
+    }
+
+    // Get background opacity of a profile, falling back to the default one
+    pub fn opacity(&self, profile_id_opt: Option<ProfileId>) -> u8 {
+        self.profile(profile_id_opt)
+            .and_then(|profile| profile.opacity)
+            .unwrap_or(self.opacity)
+    }
+
+    pub fn opacity_ratio(&self, profile_id_opt: Option<ProfileId>) -> f32 {
+        f32::from(self.opacity(profile_id_opt)) / 100.0
+    }
+
+    pub fn background_image_dim_ratio(&self) -> f32 {
+        f32::from(self.background_image_dim) / 100.0
     }
 
     // Get a sorted and adjusted for duplicates list of profile names and ids
@@ -351,11 +726,15 @@ impl Config {
         profile_names
     }
 
-    // Get current syntax theme based on dark mode
+    // Get current syntax theme based on dark mode, or the color scheme mode of the profile
     pub fn syntax_theme(&self, profile_id_opt: Option<ProfileId>) -> (String, ColorSchemeKind) {
-        let color_scheme_kind = self.color_scheme_kind();
-        let theme_name = match profile_id_opt.and_then(|profile_id| self.profiles.get(&profile_id))
-        {
+        let profile_opt = profile_id_opt.and_then(|profile_id| self.profiles.get(&profile_id));
+        let color_scheme_kind = match profile_opt.map(|profile| profile.color_scheme_mode) {
+            Some(ColorSchemeMode::Desktop) => AppTheme::System.color_scheme_kind(),
+            Some(ColorSchemeMode::Fixed(color_scheme_kind)) => color_scheme_kind,
+            Some(ColorSchemeMode::App) | None => self.color_scheme_kind(),
+        };
+        let theme_name = match profile_opt {
             Some(profile) => match color_scheme_kind {
                 ColorSchemeKind::Dark => profile.syntax_theme_dark.clone(),
                 ColorSchemeKind::Light => profile.syntax_theme_light.clone(),
@@ -387,3 +766,268 @@ impl Config {
         })[&self.font_stretch]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile_env(name: &str, value: Option<&str>) -> ProfileEnv {
+        ProfileEnv {
+            name: name.to_string(),
+            value: value.map(str::to_string),
+        }
+    }
+
+    // Look up variables in a fixed environment instead of the one of the process
+    fn var(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |name| {
+            vars.iter()
+                .find(|(var_name, _)| *var_name == name)
+                .map(|(_, value)| value.to_string())
+        }
+    }
+
+    fn expand(value: &str) -> String {
+        expand_env(value, &HashMap::new(), &[], &var(&[("VAR", "value")]))
+    }
+
+    #[test]
+    fn expand_env_references() {
+        assert_eq!(expand("$VAR"), "value");
+        assert_eq!(expand("${VAR}"), "value");
+        assert_eq!(expand("a${VAR}b"), "avalueb");
+        assert_eq!(expand("$VAR/bin"), "value/bin");
+        // The name ends at the first character that is not part of an identifier
+        assert_eq!(expand("$VAR-x"), "value-x");
+    }
+
+    #[test]
+    fn expand_env_unset() {
+        assert_eq!(expand("a$UNSET"), "a");
+        assert_eq!(expand("a${UNSET}b"), "ab");
+
+        // Variables unset by the profile are empty even if set in the environment
+        assert_eq!(
+            expand_env(
+                "a$VAR",
+                &HashMap::new(),
+                &["VAR".to_string()],
+                &var(&[("VAR", "value")])
+            ),
+            "a"
+        );
+    }
+
+    #[test]
+    fn expand_env_literal_dollar() {
+        assert_eq!(expand("$$"), "$");
+        assert_eq!(expand("$$VAR"), "$VAR");
+        assert_eq!(expand("$"), "$");
+        assert_eq!(expand("a$ b"), "a$ b");
+        assert_eq!(expand("$1"), "$1");
+        assert_eq!(expand("$-"), "$-");
+        assert_eq!(expand("${unterminated"), "${unterminated");
+    }
+
+    #[test]
+    fn environment_refers_to_profile_vars() {
+        let profile = Profile {
+            env: vec![
+                profile_env("PREFIX", Some("$BASE/local")),
+                profile_env("BIN", Some("${PREFIX}/bin")),
+                profile_env(" ", Some("ignored")),
+                profile_env("GONE", Some("value")),
+                profile_env("GONE", None),
+                profile_env("EMPTY", Some("[$GONE]")),
+                profile_env("BASE", None),
+            ],
+            ..Default::default()
+        };
+        let (set, unset) = profile.environment_with(var(&[("BASE", "/usr")]));
+        assert_eq!(set.get("PREFIX").map(String::as_str), Some("/usr/local"));
+        assert_eq!(set.get("BIN").map(String::as_str), Some("/usr/local/bin"));
+        assert_eq!(set.get("EMPTY").map(String::as_str), Some("[]"));
+        assert!(!set.contains_key("GONE"));
+        assert_eq!(set.len(), 3);
+        assert_eq!(unset, ["GONE", "BASE"]);
+    }
+
+    fn rule(host: &str, user: &str, directory: &str) -> ProfileRule {
+        ProfileRule {
+            profile_id: ProfileId(0),
+            host: host.to_string(),
+            user: user.to_string(),
+            directory: directory.to_string(),
+        }
+    }
+
+    fn location(host: Option<&str>, user: Option<&str>, directory: Option<&str>) -> ShellLocation {
+        ShellLocation {
+            host: host.map(str::to_string),
+            user: user.map(str::to_string),
+            directory: directory.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn profile_rule_directory() {
+        let src = rule("", "", "/src");
+        assert!(src.matches(&location(None, None, Some("/src"))));
+        assert!(src.matches(&location(None, None, Some("/src/"))));
+        assert!(src.matches(&location(None, None, Some("/src/project"))));
+        assert!(!src.matches(&location(None, None, Some("/srcfoo"))));
+        assert!(!src.matches(&location(None, None, Some("/"))));
+        assert!(!src.matches(&location(None, None, None)));
+
+        // Trailing slashes in the rule do not matter
+        assert!(rule("", "", "/src/").matches(&location(None, None, Some("/src/project"))));
+        assert!(!rule("", "", "/src/").matches(&location(None, None, Some("/srcfoo"))));
+    }
+
+    #[test]
+    fn profile_rule_host_user() {
+        let host = rule("server", "", "");
+        assert!(host.matches(&location(Some("SERVER"), Some("user"), Some("/"))));
+        assert!(!host.matches(&location(Some("server2"), None, None)));
+        assert!(!host.matches(&location(None, None, None)));
+
+        let all = rule(" server ", "root", "/etc");
+        assert!(all.matches(&location(Some("server"), Some("root"), Some("/etc/nginx"))));
+        assert!(!all.matches(&location(Some("server"), Some("user"), Some("/etc"))));
+        assert!(!all.matches(&location(Some("server"), Some("root"), Some("/home"))));
+    }
+
+    #[test]
+    fn profile_rule_empty() {
+        let everything = location(Some("host"), Some("user"), Some("/"));
+        assert!(!rule("", "", "").matches(&everything));
+        assert!(!rule(" ", "\t", " ").matches(&everything));
+        assert!(!rule("", "", "").matches(&ShellLocation::default()));
+    }
+
+    #[test]
+    fn close_on_exit_from_hold() {
+        let close_on_exit = |profile: &str| {
+            ron::from_str::<Profile>(profile)
+                .map(|profile| profile.close_on_exit)
+                .unwrap()
+        };
+        assert_eq!(
+            close_on_exit("(name: \"a\", hold: true)"),
+            CloseOnExit::Never
+        );
+        assert_eq!(
+            close_on_exit("(name: \"a\", hold: false)"),
+            CloseOnExit::Always
+        );
+        assert_eq!(
+            close_on_exit("(name: \"a\", close_on_exit: Clean)"),
+            CloseOnExit::Clean
+        );
+        assert_eq!(
+            close_on_exit("(name: \"a\", close_on_exit: Always)"),
+            CloseOnExit::Always
+        );
+        // Profiles without the option close the tab, like they did without hold
+        assert_eq!(close_on_exit("(name: \"a\")"), CloseOnExit::Always);
+
+        // New profiles keep the tab open
+        assert_eq!(Profile::default().close_on_exit, CloseOnExit::Never);
+
+        // Only the new name is written
+        let profile = ron::to_string(&Profile {
+            close_on_exit: CloseOnExit::Clean,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(profile.contains("close_on_exit:Clean"));
+        assert_eq!(close_on_exit(&profile), CloseOnExit::Clean);
+    }
+
+    #[test]
+    fn scrolling_history_capped() {
+        let profile_id = ProfileId(0);
+        let mut config = Config::default();
+        assert_eq!(config.scrolling_history(None), 10_000);
+
+        config.scrolling_history = u32::MAX;
+        assert_eq!(config.scrolling_history(None), MAX_SCROLLING_HISTORY);
+
+        config.profiles.insert(
+            profile_id,
+            Profile {
+                scrolling_history: Some(1_000),
+                ..Default::default()
+            },
+        );
+        assert_eq!(config.scrolling_history(Some(profile_id)), 1_000);
+    }
+
+    #[test]
+    fn syntax_theme_color_scheme_mode() {
+        let profile_id = ProfileId(0);
+        let mut config = Config {
+            app_theme: AppTheme::Light,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.syntax_theme(None),
+            (COSMIC_THEME_LIGHT.to_string(), ColorSchemeKind::Light)
+        );
+
+        let syntax_theme = |config: &Config, color_scheme_mode| {
+            let mut config = config.clone();
+            config.profiles.insert(
+                profile_id,
+                Profile {
+                    color_scheme_mode,
+                    syntax_theme_dark: "Profile Dark".to_string(),
+                    syntax_theme_light: "Profile Light".to_string(),
+                    ..Default::default()
+                },
+            );
+            config.syntax_theme(Some(profile_id))
+        };
+
+        // The application theme is used by default
+        assert_eq!(
+            syntax_theme(&config, ColorSchemeMode::App),
+            ("Profile Light".to_string(), ColorSchemeKind::Light)
+        );
+
+        // The desktop preference is used even if the application theme is fixed
+        let desktop_kind = AppTheme::System.color_scheme_kind();
+        let desktop_name = match desktop_kind {
+            ColorSchemeKind::Dark => "Profile Dark",
+            ColorSchemeKind::Light => "Profile Light",
+        };
+        assert_eq!(
+            syntax_theme(&config, ColorSchemeMode::Desktop),
+            (desktop_name.to_string(), desktop_kind)
+        );
+
+        // Fixed modes ignore both themes
+        for app_theme in [AppTheme::Dark, AppTheme::Light] {
+            config.app_theme = app_theme;
+            assert_eq!(
+                syntax_theme(&config, ColorSchemeMode::Fixed(ColorSchemeKind::Dark)),
+                ("Profile Dark".to_string(), ColorSchemeKind::Dark)
+            );
+            assert_eq!(
+                syntax_theme(&config, ColorSchemeMode::Fixed(ColorSchemeKind::Light)),
+                ("Profile Light".to_string(), ColorSchemeKind::Light)
+            );
+        }
+    }
+
+    #[test]
+    fn environment_set_after_unset() {
+        let profile = Profile {
+            env: vec![profile_env("VAR", None), profile_env("VAR", Some("value"))],
+            ..Default::default()
+        };
+        let (set, unset) = profile.environment();
+        assert_eq!(set.get("VAR").map(String::as_str), Some("value"));
+        assert!(unset.is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+use tokio::process::Command;
+
+use crate::{config::Profile, localize::LANGUAGE_SORTER};
+
+// Labels set by the tools on the containers they create
+const DISTROBOX_LABEL: &str = "manager=distrobox";
+const TOOLBOX_LABEL: &str = "com.github.containers.toolbox=true";
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ContainerKind {
+    Distrobox,
+    Toolbox,
+}
+
+impl ContainerKind {
+    fn program(self) -> &'static str {
+        match self {
+            Self::Distrobox => "distrobox",
+            Self::Toolbox => "toolbox",
+        }
+    }
+}
+
+/// Existing toolbox or distrobox container, used as a dynamic profile
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Container {
+    pub kind: ContainerKind,
+    pub name: String,
+}
+
+impl Container {
+    /// ID of the container, used by menu actions as they can not hold the container
+    pub fn id(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn profile(&self) -> Profile {
+        let program = self.kind.program();
+        Profile {
+            name: format!("{} {}", program, self.name),
+            command: format!(
+                "{} enter {}",
+                program,
+                shlex::try_quote(&self.name).unwrap_or_else(|_| self.name.as_str().into())
+            ),
+            ..Default::default()
+        }
+    }
+}
+
+// Run a tool from tool_dir, or from PATH if it is None
+async fn output(tool_dir: Option<&Path>, program: &str, args: &[&str]) -> io::Result<String> {
+    let program_path = match tool_dir {
+        Some(tool_dir) => tool_dir.join(program),
+        None => PathBuf::from(program),
+    };
+    let output = Command::new(program_path)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("{} exited with {}", program, output.status),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Container names from podman ps, one per line
+async fn podman_containers(tool_dir: Option<&Path>, label: &str) -> io::Result<Vec<String>> {
+    let filter = format!("label={}", label);
+    let stdout = output(
+        tool_dir,
+        "podman",
+        &["ps", "--all", "--filter", &filter, "--format", "{{.Names}}"],
+    )
+    .await?;
+    Ok(stdout
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// Parse the table printed by distrobox list, which has the name in the second column
+fn parse_distrobox_list(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split('|').nth(1))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Discover containers, missing tools are ignored
+pub async fn containers() -> Vec<Container> {
+    containers_from_tools(None).await
+}
+
+async fn containers_from_tools(tool_dir: Option<&Path>) -> Vec<Container> {
+    let mut containers = Vec::new();
+
+    // distrobox list also finds containers managed by docker
+    let distrobox_names = match output(tool_dir, "distrobox", &["list", "--no-color"]).await {
+        Ok(stdout) => Ok(parse_distrobox_list(&stdout)),
+        Err(_) => podman_containers(tool_dir, DISTROBOX_LABEL).await,
+    };
+    match distrobox_names {
+        Ok(names) => containers.extend(names.into_iter().map(|name| Container {
+            kind: ContainerKind::Distrobox,
+            name,
+        })),
+        Err(err) => log::debug!("failed to list distrobox containers: {}", err),
+    }
+
+    match podman_containers(tool_dir, TOOLBOX_LABEL).await {
+        Ok(names) => containers.extend(names.into_iter().map(|name| Container {
+            kind: ContainerKind::Toolbox,
+            name,
+        })),
+        Err(err) => log::debug!("failed to list toolbox containers: {}", err),
+    }
+
+    containers.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then_with(|| LANGUAGE_SORTER.compare(&a.name, &b.name))
+    });
+    containers.dedup();
+    containers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    fn container(kind: ContainerKind, name: &str) -> Container {
+        Container {
+            kind,
+            name: name.to_string(),
+        }
+    }
+
+    fn write_script(dir: &Path, name: &str, script: &str) {
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn distrobox_list() {
+        let stdout = "\
+ID           | NAME                 | STATUS                 | IMAGE
+b9f8c4d1e2a3 | fedora-40            | Up 2 hours             | registry.fedoraproject.org/fedora-toolbox:40
+4c3a6d9e8f71 | ubuntu               | Exited (0) 3 days ago  | quay.io/toolbx/ubuntu-toolbox:24.04
+
+";
+        assert_eq!(parse_distrobox_list(stdout), ["fedora-40", "ubuntu"]);
+        assert!(parse_distrobox_list("ID | NAME | STATUS | IMAGE\n").is_empty());
+        assert!(parse_distrobox_list("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn containers_from_tool_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let containers = || containers_from_tools(Some(dir.path()));
+
+        // Tools that are not installed are ignored
+        assert!(containers().await.is_empty());
+
+        // podman is used to find distrobox containers without distrobox
+        write_script(
+            dir.path(),
+            "podman",
+            "case \"$4\" in
+label=manager=distrobox) echo podman-box ;;
+label=com.github.containers.toolbox=true) printf 'fedora-toolbox-40\\n\\narch\\n' ;;
+esac
+",
+        );
+        assert_eq!(
+            containers().await,
+            [
+                container(ContainerKind::Distrobox, "podman-box"),
+                container(ContainerKind::Toolbox, "arch"),
+                container(ContainerKind::Toolbox, "fedora-toolbox-40"),
+            ]
+        );
+
+        // distrobox also lists containers that podman does not manage
+        write_script(
+            dir.path(),
+            "distrobox",
+            "echo 'ID           | NAME     | STATUS     | IMAGE'
+echo '4c3a6d9e8f71 | ubuntu   | Up 2 hours | quay.io/toolbx/ubuntu-toolbox:24.04'
+echo 'b9f8c4d1e2a3 | debian   | Created    | docker.io/library/debian:12'
+",
+        );
+        assert_eq!(
+            containers().await,
+            [
+                container(ContainerKind::Distrobox, "debian"),
+                container(ContainerKind::Distrobox, "ubuntu"),
+                container(ContainerKind::Toolbox, "arch"),
+                container(ContainerKind::Toolbox, "fedora-toolbox-40"),
+            ]
+        );
+
+        // Failing tools are ignored
+        write_script(dir.path(), "podman", "exit 125\n");
+        assert_eq!(
+            containers().await,
+            [
+                container(ContainerKind::Distrobox, "debian"),
+                container(ContainerKind::Distrobox, "ubuntu"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use alacritty_terminal::{selection::SelectionType, vi_mode::ViMotion};
+use cosmic::{
+    iced::keyboard::{Key, Modifiers},
+    iced_core::keyboard::key::Named,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CopyModeAction {
+    Bottom,
+    Exit,
+    HalfPageDown,
+    HalfPageUp,
+    Motion(ViMotion),
+    Search(bool),
+    SearchNext,
+    SearchPrevious,
+    Select(SelectionType),
+    Top,
+    Yank,
+}
+
+/// Keyboard state of the vi-style copy mode
+#[derive(Debug, Default)]
+pub struct CopyMode {
+    pending_g: bool,
+}
+
+impl CopyMode {
+    /// Translate a key press into a copy mode action, following vi key bindings
+    pub fn action(&mut self, modifiers: Modifiers, key: &Key) -> Option<CopyModeAction> {
+        let pending_g = std::mem::take(&mut self.pending_g);
+
+        if modifiers.logo() || modifiers.alt() {
+            return None;
+        }
+
+        let character = match key {
+            Key::Named(named) => {
+                return match named {
+                    Named::ArrowLeft => Some(CopyModeAction::Motion(ViMotion::Left)),
+                    Named::ArrowDown => Some(CopyModeAction::Motion(ViMotion::Down)),
+                    Named::ArrowUp => Some(CopyModeAction::Motion(ViMotion::Up)),
+                    Named::ArrowRight => Some(CopyModeAction::Motion(ViMotion::Right)),
+                    Named::Home => Some(CopyModeAction::Motion(ViMotion::First)),
+                    Named::End => Some(CopyModeAction::Motion(ViMotion::Last)),
+                    Named::Escape => Some(CopyModeAction::Exit),
+                    _ => None,
+                };
+            }
+            Key::Character(c) => c.as_str(),
+            _ => return None,
+        };
+
+        if modifiers.control() {
+            return match character {
+                "d" => Some(CopyModeAction::HalfPageDown),
+                "u" => Some(CopyModeAction::HalfPageUp),
+                "v" => Some(CopyModeAction::Select(SelectionType::Block)),
+                _ => None,
+            };
+        }
+
+        match character {
+            "h" => Some(CopyModeAction::Motion(ViMotion::Left)),
+            "j" => Some(CopyModeAction::Motion(ViMotion::Down)),
+            "k" => Some(CopyModeAction::Motion(ViMotion::Up)),
+            "l" => Some(CopyModeAction::Motion(ViMotion::Right)),
+            "w" => Some(CopyModeAction::Motion(ViMotion::SemanticRight)),
+            "b" => Some(CopyModeAction::Motion(ViMotion::SemanticLeft)),
+            "e" => Some(CopyModeAction::Motion(ViMotion::SemanticRightEnd)),
+            "W" => Some(CopyModeAction::Motion(ViMotion::WordRight)),
+            "B" => Some(CopyModeAction::Motion(ViMotion::WordLeft)),
+            "E" => Some(CopyModeAction::Motion(ViMotion::WordRightEnd)),
+            "0" => Some(CopyModeAction::Motion(ViMotion::First)),
+            "^" => Some(CopyModeAction::Motion(ViMotion::FirstOccupied)),
+            "$" => Some(CopyModeAction::Motion(ViMotion::Last)),
+            "H" => Some(CopyModeAction::Motion(ViMotion::High)),
+            "M" => Some(CopyModeAction::Motion(ViMotion::Middle)),
+            "L" => Some(CopyModeAction::Motion(ViMotion::Low)),
+            "%" => Some(CopyModeAction::Motion(ViMotion::Bracket)),
+            "{" => Some(CopyModeAction::Motion(ViMotion::ParagraphUp)),
+            "}" => Some(CopyModeAction::Motion(ViMotion::ParagraphDown)),
+            "g" => {
+                if pending_g {
+                    Some(CopyModeAction::Top)
+                } else {
+                    self.pending_g = true;
+                    None
+                }
+            }
+            "G" => Some(CopyModeAction::Bottom),
+            "v" => Some(CopyModeAction::Select(SelectionType::Simple)),
+            "V" => Some(CopyModeAction::Select(SelectionType::Lines)),
+            "y" => Some(CopyModeAction::Yank),
+            "/" => Some(CopyModeAction::Search(true)),
+            "?" => Some(CopyModeAction::Search(false)),
+            "n" => Some(CopyModeAction::SearchNext),
+            "N" => Some(CopyModeAction::SearchPrevious),
+            "i" | "q" => Some(CopyModeAction::Exit),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: Modifiers = Modifiers::empty();
+    const CTRL: Modifiers = Modifiers::CTRL;
+
+    fn character(c: &str) -> Key {
+        Key::Character(c.into())
+    }
+
+    fn check(rows: &[(Modifiers, Key, Option<CopyModeAction>)]) {
+        for (modifiers, key, expected) in rows {
+            let mut copy_mode = CopyMode::default();
+            assert_eq!(
+                copy_mode.action(*modifiers, key),
+                *expected,
+                "{:?} {:?}",
+                modifiers,
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn motions() {
+        use CopyModeAction::Motion;
+        check(&[
+            (NONE, character("h"), Some(Motion(ViMotion::Left))),
+            (NONE, character("j"), Some(Motion(ViMotion::Down))),
+            (NONE, character("k"), Some(Motion(ViMotion::Up))),
+            (NONE, character("l"), Some(Motion(ViMotion::Right))),
+            (NONE, character("w"), Some(Motion(ViMotion::SemanticRight))),
+            (NONE, character("b"), Some(Motion(ViMotion::SemanticLeft))),
+            (
+                NONE,
+                character("e"),
+                Some(Motion(ViMotion::SemanticRightEnd)),
+            ),
+            (NONE, character("W"), Some(Motion(ViMotion::WordRight))),
+            (NONE, character("B"), Some(Motion(ViMotion::WordLeft))),
+            (NONE, character("E"), Some(Motion(ViMotion::WordRightEnd))),
+            (NONE, character("0"), Some(Motion(ViMotion::First))),
+            (NONE, character("^"), Some(Motion(ViMotion::FirstOccupied))),
+            (NONE, character("$"), Some(Motion(ViMotion::Last))),
+            (NONE, character("H"), Some(Motion(ViMotion::High))),
+            (NONE, character("M"), Some(Motion(ViMotion::Middle))),
+            (NONE, character("L"), Some(Motion(ViMotion::Low))),
+            (NONE, character("%"), Some(Motion(ViMotion::Bracket))),
+            (NONE, character("{"), Some(Motion(ViMotion::ParagraphUp))),
+            (NONE, character("}"), Some(Motion(ViMotion::ParagraphDown))),
+            (
+                NONE,
+                Key::Named(Named::ArrowLeft),
+                Some(Motion(ViMotion::Left)),
+            ),
+            (
+                NONE,
+                Key::Named(Named::ArrowDown),
+                Some(Motion(ViMotion::Down)),
+            ),
+            (NONE, Key::Named(Named::ArrowUp), Some(Motion(ViMotion::Up))),
+            (
+                NONE,
+                Key::Named(Named::ArrowRight),
+                Some(Motion(ViMotion::Right)),
+            ),
+            (NONE, Key::Named(Named::Home), Some(Motion(ViMotion::First))),
+            (NONE, Key::Named(Named::End), Some(Motion(ViMotion::Last))),
+            (NONE, character("G"), Some(CopyModeAction::Bottom)),
+            (CTRL, character("d"), Some(CopyModeAction::HalfPageDown)),
+            (CTRL, character("u"), Some(CopyModeAction::HalfPageUp)),
+        ]);
+    }
+
+    #[test]
+    fn actions() {
+        check(&[
+            (
+                NONE,
+                character("v"),
+                Some(CopyModeAction::Select(SelectionType::Simple)),
+            ),
+            (
+                NONE,
+                character("V"),
+                Some(CopyModeAction::Select(SelectionType::Lines)),
+            ),
+            (
+                CTRL,
+                character("v"),
+                Some(CopyModeAction::Select(SelectionType::Block)),
+            ),
+            (NONE, character("y"), Some(CopyModeAction::Yank)),
+            (NONE, character("/"), Some(CopyModeAction::Search(true))),
+            (NONE, character("?"), Some(CopyModeAction::Search(false))),
+            (NONE, character("n"), Some(CopyModeAction::SearchNext)),
+            (NONE, character("N"), Some(CopyModeAction::SearchPrevious)),
+            (NONE, character("i"), Some(CopyModeAction::Exit)),
+            (NONE, character("q"), Some(CopyModeAction::Exit)),
+            (NONE, Key::Named(Named::Escape), Some(CopyModeAction::Exit)),
+        ]);
+    }
+
+    #[test]
+    fn ignored_keys() {
+        check(&[
+            (NONE, character("x"), None),
+            (CTRL, character("h"), None),
+            (Modifiers::ALT, character("h"), None),
+            (Modifiers::LOGO, character("j"), None),
+            (NONE, Key::Named(Named::Enter), None),
+            (NONE, Key::Unidentified, None),
+        ]);
+    }
+
+    #[test]
+    fn top_needs_two_g() {
+        let mut copy_mode = CopyMode::default();
+        assert_eq!(copy_mode.action(NONE, &character("g")), None);
+        assert_eq!(
+            copy_mode.action(NONE, &character("g")),
+            Some(CopyModeAction::Top)
+        );
+
+        // Any other key cancels the pending g
+        assert_eq!(copy_mode.action(NONE, &character("g")), None);
+        assert_eq!(
+            copy_mode.action(NONE, &character("j")),
+            Some(CopyModeAction::Motion(ViMotion::Down))
+        );
+        assert_eq!(copy_mode.action(NONE, &character("g")), None);
+    }
+}
@@ -23,14 +23,17 @@ pub fn key_binds() -> HashMap<KeyBind, Action> {
     // Standard key bindings
     bind!([Ctrl, Shift], Key::Character("A".into()), SelectAll);
     bind!([Ctrl, Shift], Key::Character("C".into()), Copy);
+    bind!([Ctrl, Shift], Key::Named(Named::Space), CopyMode);
     bind!([Ctrl], Key::Character("c".into()), CopyOrSigint);
     bind!([Ctrl, Shift], Key::Character("F".into()), Find);
     bind!([Ctrl, Shift], Key::Character("N".into()), WindowNew);
     bind!([Ctrl, Shift], Key::Character("Q".into()), WindowClose);
     bind!([Ctrl, Shift], Key::Character("T".into()), TabNew);
+    bind!([Ctrl, Shift], Key::Character("P".into()), ProfileLauncher);
     bind!([Ctrl, Shift], Key::Character("V".into()), Paste);
     bind!([Shift], Key::Named(Named::Insert), PastePrimary);
     bind!([Ctrl, Shift], Key::Character("W".into()), TabClose);
+    bind!([Ctrl, Shift], Key::Character("R".into()), TabRestart);
     bind!([Ctrl], Key::Character(",".into()), Settings);
 
     // Ctrl+Alt+D splits horizontally, Ctrl+Alt+R splits vertically, Ctrl+Shift+X maximizes split
@@ -0,0 +1,501 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use alacritty_terminal::term::TermMode;
+use cosmic::{
+    iced::keyboard::{Key, Location, Modifiers},
+    iced_core::keyboard::key::Named,
+};
+
+/// Translate a key press into the bytes that should be written to the pty.
+///
+/// Returns `None` if the key does not produce any input, for example a bare modifier key or a
+/// key combination involving super.
+pub fn encode(
+    key: &Key,
+    location: Location,
+    text: Option<&str>,
+    modifiers: Modifiers,
+    mode: TermMode,
+) -> Option<Vec<u8>> {
+    if location == Location::Numpad && mode.contains(TermMode::APP_KEYPAD) {
+        if let Some(code) = encode_app_keypad(key, modifiers) {
+            return Some(code);
+        }
+    }
+
+    match key {
+        Key::Named(named) => encode_named(*named, modifiers, mode),
+        _ => encode_text(key, text, modifiers),
+    }
+}
+
+//Implemented according to
+//https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-VT52-Style-Function-Keys
+//Like xterm, digits are only sent as application keys while Num Lock is on. With Num Lock
+//off the keypad produces navigation keys, which are encoded like the editing keypad.
+fn encode_app_keypad(key: &Key, modifiers: Modifiers) -> Option<Vec<u8>> {
+    let code = match key {
+        Key::Named(Named::Enter) => "M",
+        Key::Character(c) => match c.as_str() {
+            "*" => "j",
+            "+" => "k",
+            "," => "l",
+            "-" => "m",
+            "." => "n",
+            "/" => "o",
+            "0" => "p",
+            "1" => "q",
+            "2" => "r",
+            "3" => "s",
+            "4" => "t",
+            "5" => "u",
+            "6" => "v",
+            "7" => "w",
+            "8" => "x",
+            "9" => "y",
+            "=" => "X",
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let mod_no = modifier_number(modifiers);
+    if mod_no == 1 {
+        Some(format!("\x1B\x4F{code}").into_bytes())
+    } else {
+        Some(format!("\x1B\x4F{mod_no}{code}").into_bytes())
+    }
+}
+
+//Implemented according to
+//https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-PC-Style-Function-Keys
+fn encode_named(named: Named, modifiers: Modifiers, mode: TermMode) -> Option<Vec<u8>> {
+    let is_app_cursor = mode.contains(TermMode::APP_CURSOR);
+    let mod_no = modifier_number(modifiers);
+
+    // Modified cursor keys are sent as CSI 1;mod in both cursor modes
+    let cursor = |code: &str| {
+        if is_app_cursor || mod_no != 1 {
+            ss3(code, mod_no)
+        } else {
+            csi(code, "", mod_no)
+        }
+    };
+
+    let escape_code = match named {
+        Named::Insert => Some(csi("2", "~", mod_no)),
+        Named::Delete => Some(csi("3", "~", mod_no)),
+        Named::PageUp => Some(csi("5", "~", mod_no)),
+        Named::PageDown => Some(csi("6", "~", mod_no)),
+        Named::ArrowUp => Some(cursor("A")),
+        Named::ArrowDown => Some(cursor("B")),
+        Named::ArrowRight => Some(cursor("C")),
+        Named::ArrowLeft => Some(cursor("D")),
+        Named::End => Some(cursor("F")),
+        Named::Home => Some(cursor("H")),
+        // Keypad 5 with Num Lock off (KP_Begin)
+        Named::Clear => Some(cursor("E")),
+        Named::F1 => Some(ss3("P", mod_no)),
+        Named::F2 => Some(ss3("Q", mod_no)),
+        Named::F3 => Some(ss3("R", mod_no)),
+        Named::F4 => Some(ss3("S", mod_no)),
+        Named::F5 => Some(csi("15", "~", mod_no)),
+        Named::F6 => Some(csi("17", "~", mod_no)),
+        Named::F7 => Some(csi("18", "~", mod_no)),
+        Named::F8 => Some(csi("19", "~", mod_no)),
+        Named::F9 => Some(csi("20", "~", mod_no)),
+        Named::F10 => Some(csi("21", "~", mod_no)),
+        Named::F11 => Some(csi("23", "~", mod_no)),
+        Named::F12 => Some(csi("24", "~", mod_no)),
+        _ => None,
+    };
+    if escape_code.is_some() {
+        return escape_code;
+    }
+
+    //Special handle Enter, Escape, Backspace and Tab as described in
+    //https://sw.kovidgoyal.net/kitty/keyboard-protocol/#legacy-key-event-encoding
+    let code: &[u8] = match named {
+        Named::Backspace => {
+            if modifiers.control() {
+                b"\x08"
+            } else {
+                b"\x7f"
+            }
+        }
+        Named::Enter => b"\x0D",
+        Named::Escape => b"\x1B",
+        Named::Space => b" ",
+        Named::Tab => {
+            if modifiers.shift() {
+                b"\x1b[Z"
+            } else {
+                b"\x09"
+            }
+        }
+        _ => return None,
+    };
+    Some(alt_prefixed(code, modifiers.alt()))
+}
+
+fn encode_text(key: &Key, text: Option<&str>, modifiers: Modifiers) -> Option<Vec<u8>> {
+    let character = text.and_then(|c| c.chars().next()).unwrap_or_default();
+    match (
+        modifiers.logo(),
+        modifiers.control(),
+        modifiers.alt(),
+        modifiers.shift(),
+    ) {
+        (true, _, _, _) => {
+            // Ignore super
+            None
+        }
+        (false, true, true, _) => {
+            // Handle ctrl-alt for non-control characters
+            // and control characters 0-32
+            if !character.is_control() || (character as u32) < 32 {
+                Some(alt_prefixed(&encode_char(character), true))
+            } else {
+                None
+            }
+        }
+        (false, true, _, false) => {
+            // Handle ctrl for control characters (Ctrl-A to Ctrl-Z)
+            character.is_control().then(|| encode_char(character))
+        }
+        (false, true, _, true) => {
+            //This is normally Ctrl+Minus, but since that
+            //is taken by zoom, we send that code for
+            //Ctrl+Underline instead, like xterm and
+            //gnome-terminal
+            (key == &Key::Character("_".into())).then(|| b"\x1F".to_vec())
+        }
+        (false, false, true, _) => {
+            // Handle alt for non-control characters
+            (!character.is_control()).then(|| alt_prefixed(&encode_char(character), true))
+        }
+        (false, false, false, _) => {
+            // Handle no modifiers for non-control characters
+            (!character.is_control()).then(|| encode_char(character))
+        }
+    }
+}
+
+fn encode_char(character: char) -> Vec<u8> {
+    let mut buf = [0, 0, 0, 0];
+    character.encode_utf8(&mut buf).as_bytes().to_vec()
+}
+
+fn alt_prefixed(code: &[u8], alt: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(code.len() + 1);
+    if alt {
+        bytes.push(0x1B);
+    }
+    bytes.extend_from_slice(code);
+    bytes
+}
+
+/*
+ shift     0b1         (1)
+alt       0b10        (2)
+ctrl      0b100       (4)
+super     0b1000      (8)
+hyper     0b10000     (16)
+meta      0b100000    (32)
+caps_lock 0b1000000   (64)
+num_lock  0b10000000  (128)
+*/
+fn modifier_number(modifiers: Modifiers) -> u8 {
+    let mut mod_no = 0;
+    if modifiers.shift() {
+        mod_no |= 1;
+    }
+    if modifiers.alt() {
+        mod_no |= 2;
+    }
+    if modifiers.control() {
+        mod_no |= 4;
+    }
+    if modifiers.logo() {
+        mod_no |= 8;
+    }
+    mod_no + 1
+}
+
+#[inline(always)]
+fn csi(code: &str, suffix: &str, modifiers: u8) -> Vec<u8> {
+    if modifiers == 1 {
+        format!("\x1B[{code}{suffix}").into_bytes()
+    } else {
+        format!("\x1B[{code};{modifiers}{suffix}").into_bytes()
+    }
+}
+
+#[inline(always)]
+fn ss3(code: &str, modifiers: u8) -> Vec<u8> {
+    if modifiers == 1 {
+        format!("\x1B\x4F{code}").into_bytes()
+    } else {
+        format!("\x1B[1;{modifiers}{code}").into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NONE: Modifiers = Modifiers::empty();
+    const SHIFT: Modifiers = Modifiers::SHIFT;
+    const ALT: Modifiers = Modifiers::ALT;
+    const CTRL: Modifiers = Modifiers::CTRL;
+
+    type Row = (
+        Key,
+        Location,
+        Option<&'static str>,
+        Modifiers,
+        TermMode,
+        Option<&'static [u8]>,
+    );
+
+    fn check(rows: &[Row]) {
+        for (key, location, text, modifiers, mode, expected) in rows {
+            assert_eq!(
+                encode(key, *location, *text, *modifiers, *mode).as_deref(),
+                *expected,
+                "{key:?} {location:?} {modifiers:?} {mode:?}",
+            );
+        }
+    }
+
+    fn named(named: Named, modifiers: Modifiers, mode: TermMode, expected: &'static [u8]) -> Row {
+        (
+            Key::Named(named),
+            Location::Standard,
+            None,
+            modifiers,
+            mode,
+            Some(expected),
+        )
+    }
+
+    #[test]
+    fn cursor_keys() {
+        let normal = TermMode::empty();
+        let app = TermMode::APP_CURSOR;
+        check(&[
+            named(Named::ArrowUp, NONE, normal, b"\x1b[A"),
+            named(Named::ArrowDown, NONE, normal, b"\x1b[B"),
+            named(Named::ArrowRight, NONE, normal, b"\x1b[C"),
+            named(Named::ArrowLeft, NONE, normal, b"\x1b[D"),
+            named(Named::Home, NONE, normal, b"\x1b[H"),
+            named(Named::End, NONE, normal, b"\x1b[F"),
+            named(Named::ArrowUp, NONE, app, b"\x1bOA"),
+            named(Named::ArrowDown, NONE, app, b"\x1bOB"),
+            named(Named::ArrowRight, NONE, app, b"\x1bOC"),
+            named(Named::ArrowLeft, NONE, app, b"\x1bOD"),
+            named(Named::Home, NONE, app, b"\x1bOH"),
+            named(Named::End, NONE, app, b"\x1bOF"),
+            named(Named::ArrowUp, SHIFT, normal, b"\x1b[1;2A"),
+            named(Named::ArrowUp, ALT, normal, b"\x1b[1;3A"),
+            named(Named::ArrowUp, CTRL, normal, b"\x1b[1;5A"),
+            named(Named::ArrowLeft, CTRL | SHIFT, normal, b"\x1b[1;6D"),
+            named(Named::ArrowUp, CTRL, app, b"\x1b[1;5A"),
+            named(Named::Home, SHIFT, app, b"\x1b[1;2H"),
+            named(Named::End, CTRL, normal, b"\x1b[1;5F"),
+        ]);
+    }
+
+    #[test]
+    fn editing_keys() {
+        let normal = TermMode::empty();
+        check(&[
+            named(Named::Insert, NONE, normal, b"\x1b[2~"),
+            named(Named::Delete, NONE, normal, b"\x1b[3~"),
+            named(Named::Delete, CTRL, normal, b"\x1b[3;5~"),
+            named(Named::PageUp, NONE, normal, b"\x1b[5~"),
+            named(Named::PageDown, SHIFT, normal, b"\x1b[6;2~"),
+            named(Named::Backspace, NONE, normal, b"\x7f"),
+            named(Named::Backspace, CTRL, normal, b"\x08"),
+            named(Named::Backspace, ALT, normal, b"\x1b\x7f"),
+            named(Named::Enter, NONE, normal, b"\r"),
+            named(Named::Escape, NONE, normal, b"\x1b"),
+            named(Named::Tab, NONE, normal, b"\t"),
+            named(Named::Tab, SHIFT, normal, b"\x1b[Z"),
+        ]);
+    }
+
+    #[test]
+    fn function_keys() {
+        let normal = TermMode::empty();
+        check(&[
+            named(Named::F1, NONE, normal, b"\x1bOP"),
+            named(Named::F2, NONE, normal, b"\x1bOQ"),
+            named(Named::F3, NONE, normal, b"\x1bOR"),
+            named(Named::F4, NONE, normal, b"\x1bOS"),
+            named(Named::F5, NONE, normal, b"\x1b[15~"),
+            named(Named::F6, NONE, normal, b"\x1b[17~"),
+            named(Named::F7, NONE, normal, b"\x1b[18~"),
+            named(Named::F8, NONE, normal, b"\x1b[19~"),
+            named(Named::F9, NONE, normal, b"\x1b[20~"),
+            named(Named::F10, NONE, normal, b"\x1b[21~"),
+            named(Named::F11, NONE, normal, b"\x1b[23~"),
+            named(Named::F12, NONE, normal, b"\x1b[24~"),
+            named(Named::F1, SHIFT, normal, b"\x1b[1;2P"),
+            named(Named::F2, CTRL, normal, b"\x1b[1;5Q"),
+            named(Named::F4, ALT, normal, b"\x1b[1;3S"),
+            named(Named::F5, CTRL, normal, b"\x1b[15;5~"),
+            named(Named::F12, SHIFT, normal, b"\x1b[24;2~"),
+            named(Named::F10, CTRL | ALT, normal, b"\x1b[21;7~"),
+        ]);
+    }
+
+    #[test]
+    fn app_keypad() {
+        let keypad = TermMode::APP_KEYPAD;
+        let key = |c: &'static str, modifiers, mode, expected: &'static [u8]| -> Row {
+            (
+                Key::Character(c.into()),
+                Location::Numpad,
+                Some(c),
+                modifiers,
+                mode,
+                Some(expected),
+            )
+        };
+        check(&[
+            key("0", NONE, keypad, b"\x1bOp"),
+            key("1", NONE, keypad, b"\x1bOq"),
+            key("2", NONE, keypad, b"\x1bOr"),
+            key("3", NONE, keypad, b"\x1bOs"),
+            key("4", NONE, keypad, b"\x1bOt"),
+            key("5", NONE, keypad, b"\x1bOu"),
+            key("6", NONE, keypad, b"\x1bOv"),
+            key("7", NONE, keypad, b"\x1bOw"),
+            key("8", NONE, keypad, b"\x1bOx"),
+            key("9", NONE, keypad, b"\x1bOy"),
+            key("+", NONE, keypad, b"\x1bOk"),
+            key("-", NONE, keypad, b"\x1bOm"),
+            key("*", NONE, keypad, b"\x1bOj"),
+            key("/", NONE, keypad, b"\x1bOo"),
+            key(".", NONE, keypad, b"\x1bOn"),
+            key("5", CTRL, keypad, b"\x1bO5u"),
+            key("+", SHIFT, keypad, b"\x1bO2k"),
+            (
+                Key::Named(Named::Enter),
+                Location::Numpad,
+                Some("\r"),
+                NONE,
+                keypad,
+                Some(b"\x1bOM"),
+            ),
+            (
+                Key::Named(Named::Enter),
+                Location::Numpad,
+                Some("\r"),
+                CTRL,
+                keypad,
+                Some(b"\x1bO5M"),
+            ),
+            // KP_Begin, keypad 5 with Num Lock off
+            (
+                Key::Named(Named::Clear),
+                Location::Numpad,
+                None,
+                NONE,
+                TermMode::empty(),
+                Some(b"\x1b[E"),
+            ),
+            (
+                Key::Named(Named::Clear),
+                Location::Numpad,
+                None,
+                NONE,
+                keypad | TermMode::APP_CURSOR,
+                Some(b"\x1bOE"),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn keypad_plain_text() {
+        let keypad = TermMode::APP_KEYPAD;
+        check(&[
+            // Without application keypad mode
+            (
+                Key::Character("1".into()),
+                Location::Numpad,
+                Some("1"),
+                NONE,
+                TermMode::empty(),
+                Some(b"1"),
+            ),
+            (
+                Key::Named(Named::Enter),
+                Location::Numpad,
+                Some("\r"),
+                NONE,
+                TermMode::empty(),
+                Some(b"\r"),
+            ),
+            // Keys outside of the keypad
+            (
+                Key::Character("1".into()),
+                Location::Standard,
+                Some("1"),
+                NONE,
+                keypad,
+                Some(b"1"),
+            ),
+            (
+                Key::Named(Named::Enter),
+                Location::Standard,
+                Some("\r"),
+                NONE,
+                keypad,
+                Some(b"\r"),
+            ),
+            // Num Lock off sends navigation keys
+            (
+                Key::Named(Named::ArrowUp),
+                Location::Numpad,
+                None,
+                NONE,
+                keypad,
+                Some(b"\x1b[A"),
+            ),
+            (
+                Key::Named(Named::Home),
+                Location::Numpad,
+                None,
+                NONE,
+                keypad,
+                Some(b"\x1b[H"),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn text_keys() {
+        let normal = TermMode::empty();
+        let text = |c: &'static str, modifiers, expected: Option<&'static [u8]>| -> Row {
+            (
+                Key::Character(c.into()),
+                Location::Standard,
+                Some(c),
+                modifiers,
+                normal,
+                expected,
+            )
+        };
+        check(&[
+            text("a", NONE, Some(b"a")),
+            text("\u{e9}", NONE, Some("\u{e9}".as_bytes())),
+            text("a", ALT, Some(b"\x1ba")),
+            text("\u{1}", CTRL, Some(b"\x01")),
+            text("\u{1}", CTRL | ALT, Some(b"\x1b\x01")),
+            text("_", CTRL | SHIFT, Some(b"\x1f")),
+            text("a", Modifiers::LOGO, None),
+        ]);
+    }
+}
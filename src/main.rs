@@ -1,7 +1,7 @@
 // Copyright 2023 System76 <info@system76.com>
 // SPDX-License-Identifier: GPL-3.0-only
 
-use alacritty_terminal::{event::Event as TermEvent, term, term::color::Colors as TermColors, tty};
+use alacritty_terminal::{event::Event as TermEvent, term, tty};
 use cosmic::widget::menu::action::MenuAction;
 use cosmic::widget::menu::key_bind::KeyBind;
 use cosmic::{
@@ -13,53 +13,85 @@ use cosmic::{
         advanced::graphics::text::font_system,
         clipboard, event,
         futures::SinkExt,
-        keyboard::{Event as KeyEvent, Key, Modifiers},
+        keyboard::{key::Named, Event as KeyEvent, Key, Modifiers},
         mouse::{Button as MouseButton, Event as MouseEvent},
         subscription::{self, Subscription},
         window, Alignment, Color, Event, Length, Limits, Padding, Point,
     },
     style,
-    widget::{self, button, pane_grid, segmented_button, PaneGrid},
+    widget::{
+        self, button, color_picker::ColorPickerUpdate, pane_grid, segmented_button, PaneGrid,
+    },
     Application, ApplicationExt, Element,
 };
 use cosmic_files::dialog::{Dialog, DialogKind, DialogMessage, DialogResult};
 use cosmic_text::{fontdb::FaceInfo, Family, Stretch, Weight};
 use localize::LANGUAGE_SORTER;
+use notify::Watcher;
 use std::{
     any::TypeId,
     cmp,
     collections::{BTreeMap, BTreeSet, HashMap},
-    env, fs, process,
+    env, fs, mem,
+    path::PathBuf,
+    process,
     sync::{atomic::Ordering, Mutex},
+    time::Duration,
 };
 use tokio::sync::mpsc;
 
 use config::{
-    AppTheme, ColorScheme, ColorSchemeId, ColorSchemeKind, Config, Profile, ProfileId,
-    CONFIG_VERSION,
+    AppTheme, BackgroundImageMode, CloseOnExit, ColorScheme, ColorSchemeId, ColorSchemeKind,
+    ColorSchemeMode, Config, Profile, ProfileEnv, ProfileId, ProfileRule, ProfileRuleId,
+    CONFIG_VERSION, MAX_SCROLLING_HISTORY,
 };
 mod config;
 mod mouse_reporter;
 
+use color_scheme_editor::{ColorSchemeEditor, ColorSchemeSlot};
+mod color_scheme_editor;
+
+use color_scheme_format::ColorSchemeFormat;
+mod color_scheme_format;
+
+use copy_mode::CopyModeAction;
+mod copy_mode;
+
 use icon_cache::IconCache;
 mod icon_cache;
 
+use container::Container;
+mod container;
+
 use key_bind::key_binds;
 mod key_bind;
 
+mod key_encoder;
+
 mod localize;
 
 use menu::menu_bar;
 mod menu;
 
+mod profile_file;
+
+mod shell_integration;
+
+mod ssh_config;
+
 use terminal::{Terminal, TerminalPaneGrid, TerminalScroll};
 mod terminal;
 
 use terminal_box::terminal_box;
 mod terminal_box;
 
+use terminal_theme::TerminalTheme;
 mod terminal_theme;
 
+// Time to wait for the first prompt before typing the initial input of a profile into shells
+// without shell integration
+const INITIAL_INPUT_TIMEOUT: Duration = Duration::from_secs(1);
+
 lazy_static::lazy_static! {
     static ref ICON_CACHE: Mutex<IconCache> = Mutex::new(IconCache::new());
 }
@@ -69,6 +101,48 @@ pub fn icon_cache_get(name: &'static str, size: u16) -> widget::icon::Icon {
     icon_cache.get(name, size)
 }
 
+fn initial_input_timeout(
+    pane: pane_grid::Pane,
+    entity: segmented_button::Entity,
+) -> Command<Message> {
+    Command::perform(tokio::time::sleep(INITIAL_INPUT_TIMEOUT), move |()| {
+        message::app(Message::InitialInputTimeout(pane, entity))
+    })
+}
+
+// Builtin color scheme names are stored in the config, so only the label is translated
+fn theme_label(theme_name: &str) -> String {
+    if theme_name == config::COSMIC_THEME_DESKTOP {
+        fl!("desktop-color-scheme")
+    } else {
+        theme_name.to_string()
+    }
+}
+
+fn error_row<'a>(error: &'a str, spacing: u16) -> Element<'a, Message> {
+    widget::row::with_children(vec![
+        icon_cache_get("dialog-error-symbolic", 16)
+            .style(style::Svg::custom(|theme| {
+                let cosmic = theme.cosmic();
+                widget::svg::Appearance {
+                    color: Some(cosmic.destructive_text_color().into()),
+                }
+            }))
+            .into(),
+        widget::text(error)
+            .style(style::Text::Custom(|theme| {
+                let cosmic = theme.cosmic();
+                //TODO: re-export in libcosmic
+                iced::widget::text::Appearance {
+                    color: Some(cosmic.destructive_text_color().into()),
+                }
+            }))
+            .into(),
+    ])
+    .spacing(spacing)
+    .into()
+}
+
 /// Runs application with these settings
 #[rustfmt::skip]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -174,7 +248,9 @@ pub struct Flags {
 pub enum Action {
     About,
     ColorSchemes(ColorSchemeKind),
+    ContainerOpen(u64),
     Copy,
+    CopyMode,
     CopyOrSigint,
     CopyPrimary,
     Find,
@@ -187,11 +263,13 @@ pub enum Action {
     PaneToggleMaximized,
     Paste,
     PastePrimary,
+    ProfileLauncher,
     ProfileOpen(ProfileId),
     Profiles,
     SelectAll,
     Settings,
     ShowHeaderBar(bool),
+    SshHostOpen(u64),
     TabActivate0,
     TabActivate1,
     TabActivate2,
@@ -202,6 +280,8 @@ pub enum Action {
     TabActivate7,
     TabActivate8,
     TabClose,
+    TabColorScheme,
+    TabRestart,
     TabNew,
     TabNext,
     TabPrev,
@@ -219,7 +299,9 @@ impl Action {
             Self::ColorSchemes(color_scheme_kind) => {
                 Message::ToggleContextPage(ContextPage::ColorSchemes(*color_scheme_kind))
             }
+            Self::ContainerOpen(container_id) => Message::ContainerOpen(*container_id),
             Self::Copy => Message::Copy(entity_opt),
+            Self::CopyMode => Message::CopyMode(entity_opt),
             Self::CopyOrSigint => Message::CopyOrSigint(entity_opt),
             Self::CopyPrimary => Message::CopyPrimary(entity_opt),
             Self::Find => Message::Find(true),
@@ -232,7 +314,9 @@ impl Action {
             Self::PaneToggleMaximized => Message::PaneToggleMaximized,
             Self::Paste => Message::Paste(entity_opt),
             Self::PastePrimary => Message::PastePrimary(entity_opt),
+            Self::ProfileLauncher => Message::ProfileLauncher(true),
             Self::ProfileOpen(profile_id) => Message::ProfileOpen(*profile_id),
+            Self::SshHostOpen(host_id) => Message::SshHostOpen(*host_id),
             Self::Profiles => Message::ToggleContextPage(ContextPage::Profiles),
             Self::SelectAll => Message::SelectAll(entity_opt),
             Self::Settings => Message::ToggleContextPage(ContextPage::Settings),
@@ -247,6 +331,8 @@ impl Action {
             Self::TabActivate7 => Message::TabActivateJump(7),
             Self::TabActivate8 => Message::TabActivateJump(8),
             Self::TabClose => Message::TabClose(entity_opt),
+            Self::TabColorScheme => Message::ToggleContextPage(ContextPage::TabColorScheme),
+            Self::TabRestart => Message::TabRestart(entity_opt),
             Self::TabNew => Message::TabNew,
             Self::TabNext => Message::TabNext,
             Self::TabPrev => Message::TabPrev,
@@ -271,18 +357,39 @@ impl MenuAction for Action {
 #[derive(Clone, Debug)]
 pub enum Message {
     AppTheme(AppTheme),
+    BackgroundImage(Option<PathBuf>),
+    BackgroundImageDim(u8),
+    BackgroundImageLoaded(PathBuf, Option<widget::image::Handle>),
+    BackgroundImageMode(BackgroundImageMode),
+    BackgroundImageOpen,
+    BackgroundImageResult(DialogResult),
     ColorSchemeCollapse,
     ColorSchemeDelete(ColorSchemeKind, ColorSchemeId),
+    ColorSchemeDirChanged,
+    ColorSchemeEdit(ColorSchemeKind, ColorSchemeId),
+    ColorSchemeEditClose,
+    ColorSchemeEditDeriveBright(bool),
+    ColorSchemeEditDeriveDim(bool),
+    ColorSchemeEditPicker(ColorPickerUpdate),
+    ColorSchemeEditSlot(ColorSchemeSlot),
     ColorSchemeExpand(ColorSchemeKind, Option<ColorSchemeId>),
-    ColorSchemeExport(ColorSchemeKind, Option<ColorSchemeId>),
-    ColorSchemeExportResult(ColorSchemeKind, Option<ColorSchemeId>, DialogResult),
+    ColorSchemeExport(ColorSchemeKind, Option<ColorSchemeId>, ColorSchemeFormat),
+    ColorSchemeExportResult(
+        ColorSchemeKind,
+        Option<ColorSchemeId>,
+        ColorSchemeFormat,
+        DialogResult,
+    ),
     ColorSchemeImport(ColorSchemeKind),
     ColorSchemeImportResult(ColorSchemeKind, DialogResult),
     ColorSchemeRename(ColorSchemeKind, ColorSchemeId, String),
     ColorSchemeRenameSubmit,
     ColorSchemeTabActivate(widget::segmented_button::Entity),
     Config(Config),
+    ContainerOpen(u64),
+    Containers(Vec<Container>),
     Copy(Option<segmented_button::Entity>),
+    CopyMode(Option<segmented_button::Entity>),
     CopyOrSigint(Option<segmented_button::Entity>),
     CopyPrimary(Option<segmented_button::Entity>),
     DefaultBoldFontWeight(usize),
@@ -299,8 +406,10 @@ pub enum Message {
     FindSearchValueChanged(String),
     MiddleClick(pane_grid::Pane, Option<segmented_button::Entity>),
     FocusFollowMouse(bool),
+    InitialInputTimeout(pane_grid::Pane, segmented_button::Entity),
     Key(Modifiers, Key),
     LaunchUrl(String),
+    MinimumContrast(usize),
     Modifiers(Modifiers),
     MouseEnter(pane_grid::Pane),
     Opacity(u8),
@@ -316,27 +425,61 @@ pub enum Message {
     ProfileCollapse(ProfileId),
     ProfileCommand(ProfileId, String),
     ProfileDirectory(ProfileId, String),
+    ProfileEnvAdd(ProfileId),
+    ProfileEnvName(ProfileId, usize, String),
+    ProfileEnvRemove(ProfileId, usize),
+    ProfileEnvUnset(ProfileId, usize, bool),
+    ProfileEnvValue(ProfileId, usize, String),
     ProfileExpand(ProfileId),
-    ProfileHold(ProfileId, bool),
+    ProfileExport(Option<ProfileId>),
+    ProfileExportResult(Vec<ProfileId>, DialogResult),
+    ProfileFont(ProfileId, usize),
+    ProfileFontSize(ProfileId, usize),
+    ProfileLineHeight(ProfileId, usize),
+    ProfileCloseOnExit(ProfileId, CloseOnExit),
+    ProfileColorSchemeMode(ProfileId, ColorSchemeMode),
+    ProfileImport,
+    ProfileImportResult(DialogResult),
+    ProfileInitialInput(ProfileId, String),
+    ProfileLauncher(bool),
+    ProfileLauncherSearch(String),
+    ProfileLauncherSelect(Action),
+    ProfileLauncherSubmit,
+    ProfileLoginShell(ProfileId, bool),
     ProfileName(ProfileId, String),
     ProfileNew,
+    ProfileOpacity(ProfileId, Option<u8>),
+    ProfilePadding(ProfileId, usize),
     ProfileOpen(ProfileId),
     ProfileRemove(ProfileId),
+    ProfileRuleAdd(ProfileId),
+    ProfileRuleDirectory(ProfileRuleId, String),
+    ProfileRuleHost(ProfileRuleId, String),
+    ProfileRuleRemove(ProfileRuleId),
+    ProfileRuleUser(ProfileRuleId, String),
+    ProfileScrollingHistory(ProfileId, usize),
     ProfileSyntaxTheme(ProfileId, ColorSchemeKind, usize),
     ProfileTabTitle(ProfileId, String),
+    ScrollingHistory(usize),
     SelectAll(Option<segmented_button::Entity>),
     ShowAdvancedFontSettings(bool),
     ShowHeaderBar(bool),
+    SshHostOpen(u64),
+    SshHostPin(String),
+    SshHostsChanged,
     SyntaxTheme(ColorSchemeKind, usize),
     SystemThemeChange,
     TabActivate(segmented_button::Entity),
     TabActivateJump(usize),
     TabClose(Option<segmented_button::Entity>),
+    TabColorScheme(Option<(String, ColorSchemeKind)>),
     TabContextAction(segmented_button::Entity, Action),
     TabContextMenu(pane_grid::Pane, Option<Point>),
     TabNew,
     TabNext,
     TabPrev,
+    TabProgramColorsKeep,
+    TabProgramColorsReset,
     TermEvent(pane_grid::Pane, segmented_button::Entity, TermEvent),
     TermEventTx(mpsc::Sender<(pane_grid::Pane, segmented_button::Entity, TermEvent)>),
     ToggleContextPage(ContextPage),
@@ -355,6 +498,7 @@ pub enum ContextPage {
     ColorSchemes(ColorSchemeKind),
     Profiles,
     Settings,
+    TabColorScheme,
 }
 
 impl ContextPage {
@@ -364,6 +508,7 @@ impl ContextPage {
             Self::ColorSchemes(_color_scheme_kind) => fl!("color-schemes"),
             Self::Profiles => fl!("profiles"),
             Self::Settings => fl!("settings"),
+            Self::TabColorScheme => fl!("tab-color-scheme"),
         }
     }
 }
@@ -376,9 +521,22 @@ pub struct App {
     config: Config,
     key_binds: HashMap<KeyBind, Action>,
     app_themes: Vec<String>,
+    background_image: Option<(PathBuf, Option<widget::image::Handle>)>,
+    background_image_modes: Vec<String>,
+    close_on_exit_names: Vec<String>,
+    color_scheme_modes: Vec<String>,
     font_names: Vec<String>,
     font_size_names: Vec<String>,
     font_sizes: Vec<u16>,
+    profile_font_names: Vec<String>,
+    profile_font_size_names: Vec<String>,
+    line_height_names: Vec<String>,
+    line_heights: Vec<Option<u16>>,
+    profile_scrolling_history_names: Vec<String>,
+    scrolling_history_names: Vec<String>,
+    scrolling_histories: Vec<u32>,
+    padding_names: Vec<String>,
+    paddings: Vec<Option<u16>>,
     font_name_faces_map: BTreeMap<String, Vec<FaceInfo>>,
     all_font_weights_vals_names_map: BTreeMap<u16, String>,
     all_font_stretches_vals_names_map: BTreeMap<Stretch, String>,
@@ -389,26 +547,43 @@ pub struct App {
     zoom_adj: i8,
     zoom_step_names: Vec<String>,
     zoom_steps: Vec<u16>,
+    minimum_contrast_names: Vec<String>,
+    minimum_contrasts: Vec<Option<u8>>,
     theme_names_dark: Vec<String>,
     theme_names_light: Vec<String>,
-    themes: HashMap<(String, ColorSchemeKind), TermColors>,
+    theme_labels_dark: Vec<String>,
+    theme_labels_light: Vec<String>,
+    themes: HashMap<(String, ColorSchemeKind), TerminalTheme>,
+    builtin_themes: HashMap<(String, ColorSchemeKind), TerminalTheme>,
+    dir_themes: HashMap<(String, ColorSchemeKind), TerminalTheme>,
     context_page: ContextPage,
     dialog_opt: Option<Dialog<Message>>,
     terminal_ids: HashMap<pane_grid::Pane, widget::Id>,
     find: bool,
+    find_forwards: bool,
     find_search_id: widget::Id,
     find_search_value: String,
     term_event_tx_opt: Option<mpsc::Sender<(pane_grid::Pane, segmented_button::Entity, TermEvent)>>,
     startup_options: Option<tty::Options>,
     term_config: term::Config,
+    color_scheme_editor_opt: Option<ColorSchemeEditor>,
+    color_scheme_dir_errors: Vec<String>,
     color_scheme_errors: Vec<String>,
     color_scheme_expanded: Option<(ColorSchemeKind, Option<ColorSchemeId>)>,
     color_scheme_renaming: Option<(ColorSchemeKind, ColorSchemeId, String)>,
     color_scheme_rename_id: widget::Id,
     color_scheme_tab_model: widget::segmented_button::SingleSelectModel,
+    profile_errors: Vec<String>,
     profile_expanded: Option<ProfileId>,
+    // Values of unset environment variables, restored when they are set again
+    profile_env_unset_values: BTreeMap<(ProfileId, usize), String>,
+    profile_launcher: bool,
+    profile_launcher_search: String,
+    profile_launcher_search_id: widget::Id,
     show_advanced_font_settings: bool,
     modifiers: Modifiers,
+    ssh_hosts: Vec<String>,
+    containers: Vec<Container>,
 }
 
 impl App {
@@ -420,7 +595,18 @@ impl App {
     }
 
     fn update_color_schemes(&mut self) {
-        self.themes = terminal_theme::terminal_themes();
+        self.themes = self.builtin_themes.clone();
+        for (key, theme) in &self.dir_themes {
+            if self.themes.contains_key(key) {
+                log::debug!(
+                    "{:?} color scheme {:?} from color scheme directory ignored, using builtin one",
+                    key.1,
+                    key.0
+                );
+            } else {
+                self.themes.insert(key.clone(), *theme);
+            }
+        }
         for &color_scheme_kind in &[ColorSchemeKind::Dark, ColorSchemeKind::Light] {
             for (color_scheme_name, color_scheme_id) in
                 self.config.color_scheme_names(color_scheme_kind)
@@ -461,9 +647,60 @@ impl App {
             }
         }
         self.theme_names_dark
-            .sort_by(|a, b| LANGUAGE_SORTER.compare(a, b));
+            .sort_by(|a, b| LANGUAGE_SORTER.compare(&theme_label(a), &theme_label(b)));
         self.theme_names_light
-            .sort_by(|a, b| LANGUAGE_SORTER.compare(a, b));
+            .sort_by(|a, b| LANGUAGE_SORTER.compare(&theme_label(a), &theme_label(b)));
+        self.theme_labels_dark = self
+            .theme_names_dark
+            .iter()
+            .map(|name| theme_label(name))
+            .collect();
+        self.theme_labels_light = self
+            .theme_names_light
+            .iter()
+            .map(|name| theme_label(name))
+            .collect();
+    }
+
+    // Background images are decoded in the background only when the path changes, as large
+    // images can take a while
+    fn update_background_image(&mut self) -> Command<Message> {
+        let Some(path) = self.config.background_image.clone() else {
+            self.background_image = None;
+            return Command::none();
+        };
+        if self.background_image.as_ref().map(|(p, _)| p) == Some(&path) {
+            return Command::none();
+        }
+
+        self.background_image = Some((path.clone(), None));
+        Command::perform(
+            async move {
+                let load_path = path.clone();
+                let handle_opt =
+                    tokio::task::spawn_blocking(move || match image::open(&load_path) {
+                        Ok(image) => {
+                            let pixels = image.into_rgba8();
+                            Some(widget::image::Handle::from_pixels(
+                                pixels.width(),
+                                pixels.height(),
+                                pixels.into_raw(),
+                            ))
+                        }
+                        Err(err) => {
+                            log::warn!("failed to load background image {:?}: {}", load_path, err);
+                            None
+                        }
+                    })
+                    .await
+                    .unwrap_or_else(|err| {
+                        log::warn!("failed to load background image: {}", err);
+                        None
+                    });
+                (path, handle_opt)
+            },
+            |(path, handle_opt)| message::app(Message::BackgroundImageLoaded(path, handle_opt)),
+        )
     }
 
     fn update_config(&mut self) -> Command<Message> {
@@ -472,6 +709,9 @@ impl App {
         // Update color schemes
         self.update_color_schemes();
 
+        // Update background image
+        let background_image_command = self.update_background_image();
+
         // Update terminal window background color
         {
             let color = Color::from(theme.cosmic().background.base);
@@ -497,7 +737,10 @@ impl App {
         self.core.window.show_headerbar = self.config.show_headerbar;
 
         // Update application theme
-        cosmic::app::command::set_theme(theme)
+        Command::batch([
+            background_image_command,
+            cosmic::app::command::set_theme(theme),
+        ])
     }
 
     fn save_config(&mut self) -> Command<Message> {
@@ -529,6 +772,63 @@ impl App {
         Command::none()
     }
 
+    // Edit the color scheme open in the color scheme editor, applying changes to open terminals.
+    // Changes are only previewed until save is set, so that dragging in the color picker does
+    // not write the config for every step.
+    fn update_color_scheme_editor<F: FnOnce(&mut ColorSchemeEditor, &mut ColorScheme)>(
+        &mut self,
+        save: bool,
+        f: F,
+    ) -> Command<Message> {
+        let Some(editor) = &mut self.color_scheme_editor_opt else {
+            return Command::none();
+        };
+        let color_scheme_kind = editor.kind;
+        let Some(color_scheme) = self
+            .config
+            .color_schemes_mut(color_scheme_kind)
+            .get_mut(&editor.id)
+        else {
+            log::warn!("failed to find color scheme {:?}", editor.id);
+            return Command::none();
+        };
+
+        let old_color_scheme = color_scheme.clone();
+        f(editor, color_scheme);
+        let changed = *color_scheme != old_color_scheme;
+        if changed {
+            editor.unsaved = true;
+        }
+        let save = save && editor.unsaved;
+        if save {
+            editor.unsaved = false;
+        }
+
+        let mut commands = Vec::with_capacity(2);
+        if save {
+            commands.push(self.save_color_schemes(color_scheme_kind));
+        }
+        if changed {
+            commands.push(self.update_config());
+        }
+        Command::batch(commands)
+    }
+
+    // Close the color scheme editor, saving changes that were only previewed
+    fn close_color_scheme_editor(&mut self) -> Command<Message> {
+        match self.color_scheme_editor_opt.take() {
+            Some(editor) if editor.unsaved => self.save_color_schemes(editor.kind),
+            _ => Command::none(),
+        }
+    }
+
+    // Containers are discovered in the background as it requires running podman and distrobox
+    fn update_containers(&self) -> Command<Message> {
+        Command::perform(container::containers(), |containers| {
+            message::app(Message::Containers(containers))
+        })
+    }
+
     fn save_profiles(&mut self) -> Command<Message> {
         // Optimized for just saving profiles
         if let Some(ref config_handler) = self.config_handler {
@@ -542,8 +842,49 @@ impl App {
         Command::none()
     }
 
+    // Close the tab of a process that exited, or keep it open depending on the profile
+    fn process_exited(
+        &mut self,
+        pane: pane_grid::Pane,
+        entity: segmented_button::Entity,
+        exit_status_opt: Option<i32>,
+    ) -> Command<Message> {
+        let close = match self.pane_model.panes.get(pane) {
+            Some(tab_model) => match tab_model.data::<Mutex<Terminal>>(entity) {
+                Some(terminal) => terminal.lock().unwrap().process_exited(exit_status_opt),
+                None => false,
+            },
+            None => false,
+        };
+        if close {
+            self.update(Message::TabClose(Some(entity)))
+        } else {
+            Command::none()
+        }
+    }
+
+    fn save_profile_rules(&mut self) -> Command<Message> {
+        if let Some(ref config_handler) = self.config_handler {
+            match config_handler.set("profile_rules", &self.config.profile_rules) {
+                Ok(()) => {}
+                Err(err) => {
+                    log::error!("failed to save config: {}", err);
+                }
+            }
+        }
+        Command::none()
+    }
+
+    // Copy mode keys are only handled when the terminal has focus, so they must be ignored
+    // while the find, profile launcher, or a context drawer text input may be focused
+    fn copy_mode_keys_enabled(&self) -> bool {
+        !self.find && !self.profile_launcher && !self.core.window.show_context
+    }
+
     fn update_focus(&self) -> Command<Message> {
-        if self.find {
+        if self.profile_launcher {
+            widget::text_input::focus(self.profile_launcher_search_id.clone())
+        } else if self.find {
             widget::text_input::focus(self.find_search_id.clone())
         } else if let Some(terminal_id) = self.terminal_ids.get(&self.pane_model.focus).cloned() {
             widget::text_input::focus(terminal_id)
@@ -687,9 +1028,23 @@ impl App {
     }
 
     fn color_schemes(&self, color_scheme_kind: ColorSchemeKind) -> Element<Message> {
-        let cosmic_theme::Spacing { space_xxxs, .. } = self.core().system_theme().cosmic().spacing;
+        let spacing = self.core().system_theme().cosmic().spacing;
+        let cosmic_theme::Spacing { space_xxxs, .. } = spacing;
+
+        if let Some(editor) = &self.color_scheme_editor_opt {
+            if let Some(color_scheme) = self
+                .config
+                .color_schemes(color_scheme_kind)
+                .get(&editor.id)
+                .filter(|_| editor.kind == color_scheme_kind)
+            {
+                return editor.view(color_scheme, spacing);
+            }
+        }
 
-        let mut sections = Vec::with_capacity(3 + self.color_scheme_errors.len());
+        let mut sections = Vec::with_capacity(
+            3 + self.color_scheme_dir_errors.len() + self.color_scheme_errors.len(),
+        );
 
         sections.push(
             widget::tab_bar::horizontal(&self.color_scheme_tab_model)
@@ -769,30 +1124,12 @@ impl App {
             .into(),
         );
 
-        for error in &self.color_scheme_errors {
-            sections.push(
-                widget::row::with_children(vec![
-                    icon_cache_get("dialog-error-symbolic", 16)
-                        .style(style::Svg::custom(|theme| {
-                            let cosmic = theme.cosmic();
-                            widget::svg::Appearance {
-                                color: Some(cosmic.destructive_text_color().into()),
-                            }
-                        }))
-                        .into(),
-                    widget::text(error)
-                        .style(style::Text::Custom(|theme| {
-                            let cosmic = theme.cosmic();
-                            //TODO: re-export in libcosmic
-                            iced::widget::text::Appearance {
-                                color: Some(cosmic.destructive_text_color().into()),
-                            }
-                        }))
-                        .into(),
-                ])
-                .spacing(space_xxxs)
-                .into(),
-            );
+        for error in self
+            .color_scheme_dir_errors
+            .iter()
+            .chain(self.color_scheme_errors.iter())
+        {
+            sections.push(error_row(error, space_xxxs));
         }
 
         widget::settings::view_column(sections).into()
@@ -821,6 +1158,10 @@ impl App {
                 profiles_section = profiles_section.add(
                     widget::settings::item::builder(profile_name).control(
                         widget::row::with_children(vec![
+                            widget::button(icon_cache_get("document-save-symbolic", 16))
+                                .on_press(Message::ProfileExport(Some(profile_id)))
+                                .style(style::Button::Icon)
+                                .into(),
                             widget::button(icon_cache_get("edit-delete-symbolic", 16))
                                 .on_press(Message::ProfileRemove(profile_id))
                                 .style(style::Button::Icon)
@@ -849,60 +1190,117 @@ impl App {
                         .theme_names_light
                         .iter()
                         .position(|theme_name| theme_name == &profile.syntax_theme_light);
+                    let color_scheme_mode_selected = match profile.color_scheme_mode {
+                        ColorSchemeMode::App => 0,
+                        ColorSchemeMode::Desktop => 1,
+                        ColorSchemeMode::Fixed(ColorSchemeKind::Dark) => 2,
+                        ColorSchemeMode::Fixed(ColorSchemeKind::Light) => 3,
+                    };
 
-                    let expanded_section = widget::settings::view_section("")
-                        .add(
-                            widget::column::with_children(vec![
-                                widget::column::with_children(vec![
-                                    widget::text(fl!("name")).into(),
-                                    widget::text_input("", &profile.name)
-                                        .on_input(move |text| {
-                                            Message::ProfileName(profile_id, text)
-                                        })
-                                        .into(),
-                                ])
-                                .spacing(space_xxxs)
+                    let default_opacity = self.config.opacity;
+
+                    let mut general_column = widget::column::with_children(vec![
+                        widget::column::with_children(vec![
+                            widget::text(fl!("name")).into(),
+                            widget::text_input("", &profile.name)
+                                .on_input(move |text| Message::ProfileName(profile_id, text))
                                 .into(),
-                                widget::column::with_children(vec![
-                                    widget::text(fl!("command-line")).into(),
-                                    widget::text_input("", &profile.command)
-                                        .on_input(move |text| {
-                                            Message::ProfileCommand(profile_id, text)
-                                        })
-                                        .into(),
-                                ])
-                                .spacing(space_xxxs)
+                        ])
+                        .spacing(space_xxxs)
+                        .into(),
+                        widget::column::with_children(vec![
+                            widget::text(fl!("command-line")).into(),
+                            widget::text_input("", &profile.command)
+                                .on_input(move |text| Message::ProfileCommand(profile_id, text))
                                 .into(),
+                        ])
+                        .spacing(space_xxxs)
+                        .into(),
+                    ])
+                    .padding([0, space_s])
+                    .spacing(space_xs);
+                    // Login shells are only started when no command is set
+                    if profile.command.trim().is_empty() {
+                        general_column = general_column.push(
+                            widget::row::with_children(vec![
                                 widget::column::with_children(vec![
-                                    widget::text(fl!("working-directory")).into(),
-                                    widget::text_input("", &profile.working_directory)
-                                        .on_input(move |text| {
-                                            Message::ProfileDirectory(profile_id, text)
-                                        })
-                                        .into(),
+                                    widget::text(fl!("login-shell")).into(),
+                                    widget::text::caption(fl!("login-shell-description")).into(),
                                 ])
                                 .spacing(space_xxxs)
                                 .into(),
-                                widget::column::with_children(vec![
-                                    widget::text(fl!("tab-title")).into(),
-                                    widget::text_input("", &profile.tab_title)
-                                        .on_input(move |text| {
-                                            Message::ProfileTabTitle(profile_id, text)
-                                        })
-                                        .into(),
-                                    widget::text::caption(fl!("tab-title-description")).into(),
-                                ])
-                                .spacing(space_xxxs)
+                                widget::horizontal_space(Length::Fill).into(),
+                                widget::toggler(None, profile.login_shell, move |t| {
+                                    Message::ProfileLoginShell(profile_id, t)
+                                })
                                 .into(),
                             ])
-                            .padding([0, space_s])
-                            .spacing(space_xs),
+                            .align_items(Alignment::Center),
+                        );
+                    }
+                    general_column = general_column
+                        .push(
+                            widget::column::with_children(vec![
+                                widget::text(fl!("initial-input")).into(),
+                                widget::text_input("", &profile.initial_input)
+                                    .on_input(move |text| {
+                                        Message::ProfileInitialInput(profile_id, text)
+                                    })
+                                    .into(),
+                                widget::text::caption(fl!("initial-input-description")).into(),
+                            ])
+                            .spacing(space_xxxs),
+                        )
+                        .push(
+                            widget::column::with_children(vec![
+                                widget::text(fl!("working-directory")).into(),
+                                widget::text_input("", &profile.working_directory)
+                                    .on_input(move |text| {
+                                        Message::ProfileDirectory(profile_id, text)
+                                    })
+                                    .into(),
+                            ])
+                            .spacing(space_xxxs),
+                        )
+                        .push(
+                            widget::column::with_children(vec![
+                                widget::text(fl!("tab-title")).into(),
+                                widget::text_input("", &profile.tab_title)
+                                    .on_input(move |text| {
+                                        Message::ProfileTabTitle(profile_id, text)
+                                    })
+                                    .into(),
+                                widget::text::caption(fl!("tab-title-description")).into(),
+                            ])
+                            .spacing(space_xxxs),
+                        );
+
+                    let mut expanded_section = widget::settings::view_section("")
+                        .add(general_column)
+                        .add(
+                            widget::settings::item::builder(fl!("color-scheme-mode")).control(
+                                widget::dropdown(
+                                    &self.color_scheme_modes,
+                                    Some(color_scheme_mode_selected),
+                                    move |index| {
+                                        Message::ProfileColorSchemeMode(
+                                            profile_id,
+                                            match index {
+                                                1 => ColorSchemeMode::Desktop,
+                                                2 => ColorSchemeMode::Fixed(ColorSchemeKind::Dark),
+                                                3 => ColorSchemeMode::Fixed(ColorSchemeKind::Light),
+                                                _ => ColorSchemeMode::App,
+                                            },
+                                        )
+                                    },
+                                ),
+                            ),
                         )
                         .add(
                             //TODO: rename to color-scheme-dark?
                             widget::settings::item::builder(fl!("syntax-dark")).control(
                                 widget::dropdown(
-                                    &self.theme_names_dark,
+                                    &self.theme_labels_dark,
                                     dark_selected,
                                     move |theme_i| {
                                         Message::ProfileSyntaxTheme(
@@ -918,7 +1316,7 @@ impl App {
                             //TODO: rename to color-scheme-light?
                             widget::settings::item::builder(fl!("syntax-light")).control(
                                 widget::dropdown(
-                                    &self.theme_names_light,
+                                    &self.theme_labels_light,
                                     light_selected,
                                     move |theme_i| {
                                         Message::ProfileSyntaxTheme(
@@ -929,7 +1327,98 @@ impl App {
                                     },
                                 ),
                             ),
+                        );
+
+                    let font_selected = match &profile.font_name {
+                        Some(font_name) => self
+                            .font_names
+                            .iter()
+                            .position(|x| x == font_name)
+                            .map(|index| index + 1),
+                        None => Some(0),
+                    };
+                    let font_size_selected = match profile.font_size {
+                        Some(font_size) => self
+                            .font_sizes
+                            .iter()
+                            .position(|x| *x == font_size)
+                            .map(|index| index + 1),
+                        None => Some(0),
+                    };
+                    let line_height_selected = self
+                        .line_heights
+                        .iter()
+                        .position(|x| *x == profile.line_height_mul_100);
+                    let padding_selected = self.paddings.iter().position(|x| *x == profile.padding);
+                    let scrolling_history_selected = match profile.scrolling_history {
+                        Some(scrolling_history) => self
+                            .scrolling_histories
+                            .iter()
+                            .position(|x| *x == scrolling_history.min(MAX_SCROLLING_HISTORY))
+                            .map(|index| index + 1),
+                        None => Some(0),
+                    };
+                    expanded_section = expanded_section
+                        .add(
+                            widget::settings::item::builder(fl!("default-font")).control(
+                                widget::dropdown(
+                                    &self.profile_font_names,
+                                    font_selected,
+                                    move |index| Message::ProfileFont(profile_id, index),
+                                ),
+                            ),
+                        )
+                        .add(
+                            widget::settings::item::builder(fl!("default-font-size")).control(
+                                widget::dropdown(
+                                    &self.profile_font_size_names,
+                                    font_size_selected,
+                                    move |index| Message::ProfileFontSize(profile_id, index),
+                                ),
+                            ),
                         )
+                        .add(widget::settings::item::builder(fl!("line-height")).control(
+                            widget::dropdown(
+                                &self.line_height_names,
+                                line_height_selected,
+                                move |index| Message::ProfileLineHeight(profile_id, index),
+                            ),
+                        ))
+                        .add(widget::settings::item::builder(fl!("padding")).control(
+                            widget::dropdown(&self.padding_names, padding_selected, move |index| {
+                                Message::ProfilePadding(profile_id, index)
+                            }),
+                        ))
+                        .add(widget::settings::item::builder(fl!("scrollback")).control(
+                            widget::dropdown(
+                                &self.profile_scrolling_history_names,
+                                scrolling_history_selected,
+                                move |index| Message::ProfileScrollingHistory(profile_id, index),
+                            ),
+                        ))
+                        .add(
+                            widget::settings::item::builder(fl!("custom-opacity")).toggler(
+                                profile.opacity.is_some(),
+                                move |t| {
+                                    Message::ProfileOpacity(
+                                        profile_id,
+                                        t.then_some(default_opacity),
+                                    )
+                                },
+                            ),
+                        );
+
+                    if let Some(opacity) = profile.opacity {
+                        expanded_section = expanded_section.add(
+                            widget::settings::item::builder(fl!("opacity"))
+                                .description(format!("{}%", opacity))
+                                .control(widget::slider(0..=100, opacity, move |opacity| {
+                                    Message::ProfileOpacity(profile_id, Some(opacity))
+                                })),
+                        );
+                    }
+
+                    expanded_section = expanded_section
                         .add(
                             widget::settings::item::builder(fl!("make-default")).control(
                                 widget::toggler(
@@ -940,22 +1429,116 @@ impl App {
                             ),
                         )
                         .add(
+                            widget::settings::item::builder(fl!("close-on-exit")).control(
+                                widget::dropdown(
+                                    &self.close_on_exit_names,
+                                    Some(match profile.close_on_exit {
+                                        CloseOnExit::Never => 0,
+                                        CloseOnExit::Always => 1,
+                                        CloseOnExit::Clean => 2,
+                                    }),
+                                    move |index| {
+                                        Message::ProfileCloseOnExit(
+                                            profile_id,
+                                            match index {
+                                                1 => CloseOnExit::Always,
+                                                2 => CloseOnExit::Clean,
+                                                _ => CloseOnExit::Never,
+                                            },
+                                        )
+                                    },
+                                ),
+                            ),
+                        );
+
+                    let mut env_column = widget::column::with_capacity(profile.env.len() + 2)
+                        .push(widget::text(fl!("environment")))
+                        .push(widget::text::caption(fl!("environment-description")))
+                        .spacing(space_xxxs);
+                    for (env_i, profile_env) in profile.env.iter().enumerate() {
+                        env_column = env_column.push(
                             widget::row::with_children(vec![
-                                widget::column::with_children(vec![
-                                    widget::text(fl!("hold")).into(),
-                                    widget::text::caption(fl!("remain-open")).into(),
-                                ])
-                                .spacing(space_xxxs)
+                                widget::text_input(fl!("variable"), &profile_env.name)
+                                    .on_input(move |text| {
+                                        Message::ProfileEnvName(profile_id, env_i, text)
+                                    })
+                                    .width(Length::FillPortion(1))
+                                    .into(),
+                                widget::text_input(
+                                    if profile_env.value.is_some() {
+                                        fl!("value")
+                                    } else {
+                                        fl!("unset")
+                                    },
+                                    profile_env.value.as_deref().unwrap_or_default(),
+                                )
+                                .on_input(move |text| {
+                                    Message::ProfileEnvValue(profile_id, env_i, text)
+                                })
+                                .width(Length::FillPortion(2))
                                 .into(),
-                                widget::horizontal_space(Length::Fill).into(),
-                                widget::toggler(None, profile.hold, move |t| {
-                                    Message::ProfileHold(profile_id, t)
+                                widget::text(fl!("unset")).into(),
+                                widget::toggler(None, profile_env.value.is_none(), move |t| {
+                                    Message::ProfileEnvUnset(profile_id, env_i, t)
                                 })
                                 .into(),
+                                widget::button(icon_cache_get("edit-delete-symbolic", 16))
+                                    .on_press(Message::ProfileEnvRemove(profile_id, env_i))
+                                    .style(style::Button::Icon)
+                                    .into(),
+                            ])
+                            .align_items(Alignment::Center)
+                            .spacing(space_xxs),
+                        );
+                    }
+                    env_column = env_column.push(widget::row::with_children(vec![
+                        widget::horizontal_space(Length::Fill).into(),
+                        widget::button::standard(fl!("add-variable"))
+                            .on_press(Message::ProfileEnvAdd(profile_id))
+                            .into(),
+                    ]));
+                    expanded_section = expanded_section.add(env_column.padding([0, space_s]));
+
+                    let mut rules_column = widget::column::with_capacity(3)
+                        .push(widget::text(fl!("switch-rules")))
+                        .push(widget::text::caption(fl!("switch-rules-description")))
+                        .spacing(space_xxxs);
+                    for (&rule_id, rule) in self.config.profile_rules.iter() {
+                        if rule.profile_id != profile_id {
+                            continue;
+                        }
+                        rules_column = rules_column.push(
+                            widget::row::with_children(vec![
+                                widget::text_input(fl!("host"), &rule.host)
+                                    .on_input(move |text| Message::ProfileRuleHost(rule_id, text))
+                                    .width(Length::FillPortion(1))
+                                    .into(),
+                                widget::text_input(fl!("user"), &rule.user)
+                                    .on_input(move |text| Message::ProfileRuleUser(rule_id, text))
+                                    .width(Length::FillPortion(1))
+                                    .into(),
+                                widget::text_input(fl!("directory"), &rule.directory)
+                                    .on_input(move |text| {
+                                        Message::ProfileRuleDirectory(rule_id, text)
+                                    })
+                                    .width(Length::FillPortion(2))
+                                    .into(),
+                                widget::button(icon_cache_get("edit-delete-symbolic", 16))
+                                    .on_press(Message::ProfileRuleRemove(rule_id))
+                                    .style(style::Button::Icon)
+                                    .into(),
                             ])
                             .align_items(Alignment::Center)
-                            .padding([0, space_s]),
+                            .spacing(space_xxs),
                         );
+                    }
+                    rules_column = rules_column.push(widget::row::with_children(vec![
+                        widget::horizontal_space(Length::Fill).into(),
+                        widget::button::standard(fl!("add-rule"))
+                            .on_press(Message::ProfileRuleAdd(profile_id))
+                            .into(),
+                    ]));
+                    expanded_section = expanded_section.add(rules_column.padding([0, space_s]));
 
                     let padding = Padding {
                         top: 0.0,
@@ -972,16 +1555,129 @@ impl App {
 
         let add_profile = widget::row::with_children(vec![
             widget::horizontal_space(Length::Fill).into(),
+            widget::button::standard(fl!("import"))
+                .on_press(Message::ProfileImport)
+                .into(),
+            widget::button::standard(fl!("export-all"))
+                .on_press_maybe(
+                    (!self.config.profiles.is_empty()).then_some(Message::ProfileExport(None)),
+                )
+                .into(),
             widget::button::standard(fl!("add-profile"))
                 .on_press(Message::ProfileNew)
                 .into(),
-        ]);
+        ])
+        .spacing(space_xxs);
         sections.push(add_profile.into());
 
+        for error in &self.profile_errors {
+            sections.push(error_row(error, space_xxxs));
+        }
+
+        // Dynamic profiles can be pinned to customize them
+        let mut ssh_section = widget::settings::view_section(fl!("ssh-hosts"));
+        let mut has_ssh_hosts = false;
+        for host in &self.ssh_hosts {
+            let name = ssh_config::ssh_profile(host).name;
+            if self
+                .config
+                .profiles
+                .values()
+                .any(|profile| profile.name == name)
+            {
+                continue;
+            }
+            ssh_section = ssh_section.add(widget::settings::item::builder(name).control(
+                widget::button::standard(fl!("pin")).on_press(Message::SshHostPin(host.clone())),
+            ));
+            has_ssh_hosts = true;
+        }
+        if has_ssh_hosts {
+            sections.push(ssh_section.into());
+        }
+
+        widget::settings::view_column(sections).into()
+    }
+
+    fn tab_color_scheme(&self) -> Element<Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_s, ..
+        } = self.core().system_theme().cosmic().spacing;
+
+        let color_scheme_override = self.pane_model.active().and_then(|tab_model| {
+            let terminal = tab_model.data::<Mutex<Terminal>>(tab_model.active())?;
+            let terminal = terminal.lock().unwrap();
+            terminal.color_scheme_override.clone()
+        });
+
+        let item = |label: String, selected: bool, message: Message| -> Element<Message> {
+            widget::menu::menu_button(vec![
+                widget::text(label).into(),
+                widget::horizontal_space(Length::Fill).into(),
+                if selected {
+                    icon_cache_get("object-select-symbolic", 16).into()
+                } else {
+                    widget::Space::with_width(Length::Fixed(16.0)).into()
+                },
+            ])
+            .on_press(message)
+            .into()
+        };
+
+        let mut sections = Vec::with_capacity(3);
+        sections.push(
+            widget::settings::view_section("")
+                .add(item(
+                    fl!("profile-color-scheme"),
+                    color_scheme_override.is_none(),
+                    Message::TabColorScheme(None),
+                ))
+                .into(),
+        );
+        for (color_scheme_kind, title) in [
+            (ColorSchemeKind::Dark, fl!("dark")),
+            (ColorSchemeKind::Light, fl!("light")),
+        ] {
+            let mut section = widget::settings::view_section(title);
+            for theme_name in self.theme_names(color_scheme_kind) {
+                let key = (theme_name.clone(), color_scheme_kind);
+                section = section.add(item(
+                    theme_label(theme_name),
+                    color_scheme_override.as_ref() == Some(&key),
+                    Message::TabColorScheme(Some(key)),
+                ));
+            }
+            sections.push(section.into());
+        }
+        sections.push(
+            widget::settings::view_section(fl!("program-colors"))
+                .add(
+                    widget::column::with_children(vec![
+                        widget::text::caption(fl!("program-colors-description")).into(),
+                        widget::row::with_children(vec![
+                            widget::horizontal_space(Length::Fill).into(),
+                            widget::button::standard(fl!("reset"))
+                                .on_press(Message::TabProgramColorsReset)
+                                .into(),
+                            widget::button::standard(fl!("keep"))
+                                .on_press(Message::TabProgramColorsKeep)
+                                .into(),
+                        ])
+                        .spacing(space_xxs)
+                        .into(),
+                    ])
+                    .padding([0, space_s])
+                    .spacing(space_xxs),
+                )
+                .into(),
+        );
+
         widget::settings::view_column(sections).into()
     }
 
     fn settings(&self) -> Element<Message> {
+        let cosmic_theme::Spacing { space_xxs, .. } = self.core().system_theme().cosmic().spacing;
+
         let app_theme_selected = match self.config.app_theme {
             AppTheme::Dark => 1,
             AppTheme::Light => 2,
@@ -1026,8 +1722,12 @@ impl App {
             .zoom_steps
             .iter()
             .position(|zoom_step| zoom_step == &self.config.font_size_zoom_step_mul_100);
+        let minimum_contrast_selected = self
+            .minimum_contrasts
+            .iter()
+            .position(|minimum_contrast| minimum_contrast == &self.config.minimum_contrast_mul_10);
 
-        let appearance_section = widget::settings::view_section(fl!("appearance"))
+        let mut appearance_section = widget::settings::view_section(fl!("appearance"))
             .add(
                 widget::settings::item::builder(fl!("theme")).control(widget::dropdown(
                     &self.app_themes,
@@ -1044,7 +1744,7 @@ impl App {
             .add(
                 //TODO: rename to color-scheme-dark?
                 widget::settings::item::builder(fl!("syntax-dark")).control(widget::dropdown(
-                    &self.theme_names_dark,
+                    &self.theme_labels_dark,
                     dark_selected,
                     move |index| Message::SyntaxTheme(ColorSchemeKind::Dark, index),
                 )),
@@ -1052,7 +1752,7 @@ impl App {
             .add(
                 //TODO: rename to color-scheme-light?
                 widget::settings::item::builder(fl!("syntax-light")).control(widget::dropdown(
-                    &self.theme_names_light,
+                    &self.theme_labels_light,
                     light_selected,
                     move |index| Message::SyntaxTheme(ColorSchemeKind::Light, index),
                 )),
@@ -1064,31 +1764,99 @@ impl App {
                     }),
                 ),
             )
+            .add(
+                widget::settings::item::builder(fl!("minimum-contrast"))
+                    .description(fl!("minimum-contrast-description"))
+                    .control(widget::dropdown(
+                        &self.minimum_contrast_names,
+                        minimum_contrast_selected,
+                        Message::MinimumContrast,
+                    )),
+            )
             .add(
                 widget::settings::item::builder(fl!("opacity"))
                     .description(format!("{}%", self.config.opacity))
                     .control(widget::slider(0..=100, self.config.opacity, |opacity| {
                         Message::Opacity(opacity)
                     })),
-            );
-
-        let mut font_section = widget::settings::view_section(fl!("font"))
-            .add(
-                widget::settings::item::builder(fl!("default-font")).control(widget::dropdown(
-                    &self.font_names,
-                    font_selected,
-                    Message::DefaultFont,
-                )),
             )
             .add(
-                widget::settings::item::builder(fl!("default-font-size")).control(
-                    widget::dropdown(&self.font_size_names, font_size_selected, |index| {
-                        Message::DefaultFontSize(index)
-                    }),
-                ),
-            )
-            .add(
-                widget::settings::item::builder(fl!("advanced-font-settings")).control(
+                widget::settings::item::builder(fl!("background-image"))
+                    .description(
+                        self.config
+                            .background_image
+                            .as_ref()
+                            .and_then(|path| path.file_name())
+                            .map_or_else(|| fl!("none"), |name| name.to_string_lossy().to_string()),
+                    )
+                    .control(
+                        widget::row::with_children(vec![
+                            widget::button::standard(fl!("choose"))
+                                .on_press(Message::BackgroundImageOpen)
+                                .into(),
+                            widget::button::standard(fl!("remove"))
+                                .on_press_maybe(
+                                    self.config
+                                        .background_image
+                                        .is_some()
+                                        .then_some(Message::BackgroundImage(None)),
+                                )
+                                .into(),
+                        ])
+                        .spacing(space_xxs),
+                    ),
+            );
+
+        if self.config.background_image.is_some() {
+            let background_image_mode_selected = match self.config.background_image_mode {
+                BackgroundImageMode::Scaled => 0,
+                BackgroundImageMode::Tiled => 1,
+                BackgroundImageMode::Centered => 2,
+            };
+            appearance_section = appearance_section
+                .add(
+                    widget::settings::item::builder(fl!("background-image-mode")).control(
+                        widget::dropdown(
+                            &self.background_image_modes,
+                            Some(background_image_mode_selected),
+                            |index| {
+                                Message::BackgroundImageMode(match index {
+                                    1 => BackgroundImageMode::Tiled,
+                                    2 => BackgroundImageMode::Centered,
+                                    _ => BackgroundImageMode::Scaled,
+                                })
+                            },
+                        ),
+                    ),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("background-image-dim"))
+                        .description(format!("{}%", self.config.background_image_dim))
+                        .control(widget::slider(
+                            0..=100,
+                            self.config.background_image_dim,
+                            Message::BackgroundImageDim,
+                        )),
+                );
+        }
+
+        let mut font_section = widget::settings::view_section(fl!("font"))
+            .add(
+                widget::settings::item::builder(fl!("default-font")).control(widget::dropdown(
+                    &self.font_names,
+                    font_selected,
+                    Message::DefaultFont,
+                )),
+            )
+            .add(
+                widget::settings::item::builder(fl!("default-font-size")).control(
+                    widget::dropdown(&self.font_size_names, font_size_selected, |index| {
+                        Message::DefaultFontSize(index)
+                    }),
+                ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("advanced-font-settings")).control(
                     if self.show_advanced_font_settings {
                         widget::button(icon_cache_get("go-up-symbolic", 16))
                             .on_press(Message::ShowAdvancedFontSettings(false))
@@ -1160,11 +1928,25 @@ impl App {
                 .toggler(self.config.focus_follow_mouse, Message::FocusFollowMouse),
         );
 
-        let advanced_section = widget::settings::view_section(fl!("advanced")).add(
-            widget::settings::item::builder(fl!("show-headerbar"))
-                .description(fl!("show-header-description"))
-                .toggler(self.config.show_headerbar, Message::ShowHeaderBar),
-        );
+        let scrolling_history_selected = self
+            .scrolling_histories
+            .iter()
+            .position(|x| *x == self.config.scrolling_history.min(MAX_SCROLLING_HISTORY));
+        let advanced_section = widget::settings::view_section(fl!("advanced"))
+            .add(
+                widget::settings::item::builder(fl!("show-headerbar"))
+                    .description(fl!("show-header-description"))
+                    .toggler(self.config.show_headerbar, Message::ShowHeaderBar),
+            )
+            .add(
+                widget::settings::item::builder(fl!("scrollback"))
+                    .description(fl!("scrollback-description"))
+                    .control(widget::dropdown(
+                        &self.scrolling_history_names,
+                        scrolling_history_selected,
+                        Message::ScrollingHistory,
+                    )),
+            );
 
         widget::settings::view_column(vec![
             appearance_section.into(),
@@ -1174,6 +1956,25 @@ impl App {
         ])
         .into()
     }
+    // Entries of the profile launcher matching the search, case insensitive
+    fn profile_launcher_items(&self) -> Vec<(String, Action)> {
+        let search = self.profile_launcher_search.trim().to_lowercase();
+        let mut items = menu::launcher_items(&self.config, &self.ssh_hosts, &self.containers);
+        if !search.is_empty() {
+            items.retain(|(name, _)| name.to_lowercase().contains(&search));
+        }
+        items
+    }
+
+    fn profile_launcher(&self) -> Element<Message> {
+        menu::profile_launcher(
+            self.profile_launcher_items(),
+            &self.key_binds,
+            &self.profile_launcher_search,
+            self.profile_launcher_search_id.clone(),
+        )
+    }
+
     fn get_default_profile(&self) -> Option<ProfileId> {
         self.config.default_profile
     }
@@ -1182,14 +1983,31 @@ impl App {
         &mut self,
         pane: pane_grid::Pane,
         profile_id_opt: Option<ProfileId>,
+    ) -> Command<Message> {
+        let profile_opt = profile_id_opt
+            .and_then(|profile_id| self.config.profiles.get(&profile_id))
+            .cloned();
+        self.create_and_focus_new_terminal_with_profile(pane, profile_id_opt, profile_opt, None)
+    }
+
+    // Dynamic profiles are not part of the config and have no profile ID, terminals running in
+    // a container show its name in the tab title
+    fn create_and_focus_new_terminal_with_profile(
+        &mut self,
+        pane: pane_grid::Pane,
+        profile_id_opt: Option<ProfileId>,
+        profile_opt: Option<Profile>,
+        container_opt: Option<String>,
     ) -> Command<Message> {
         self.pane_model.focus = pane;
+        let mut initial_input_command = Command::none();
         match &self.term_event_tx_opt {
             Some(term_event_tx) => {
-                let colors = self
+                let (theme_name, color_scheme_kind) = self.config.syntax_theme(profile_id_opt);
+                let theme_opt = self
                     .themes
-                    .get(&self.config.syntax_theme(profile_id_opt))
-                    .or_else(|| match self.config.color_scheme_kind() {
+                    .get(&(theme_name, color_scheme_kind))
+                    .or_else(|| match color_scheme_kind {
                         ColorSchemeKind::Dark => self
                             .themes
                             .get(&(config::COSMIC_THEME_DARK.to_string(), ColorSchemeKind::Dark)),
@@ -1198,21 +2016,47 @@ impl App {
                             ColorSchemeKind::Light,
                         )),
                     });
-                match colors {
-                    Some(colors) => {
+                match theme_opt {
+                    Some(theme) => {
                         let current_pane = self.pane_model.focus;
                         if let Some(tab_model) = self.pane_model.active_mut() {
+                            let close_on_exit_opt =
+                                profile_opt.as_ref().map(|profile| profile.close_on_exit);
+                            let initial_input = profile_opt
+                                .as_ref()
+                                .map(|profile| profile.initial_input.clone())
+                                .unwrap_or_default();
                             // Use the profile options, startup options, or defaults
-                            let (options, tab_title_override) = match profile_id_opt
-                                .and_then(|profile_id| self.config.profiles.get(&profile_id))
-                            {
+                            let (options, tab_title_override) = match &profile_opt {
                                 Some(profile) => {
-                                    let mut shell = None;
-                                    if let Some(mut args) = shlex::split(&profile.command) {
-                                        if !args.is_empty() {
-                                            let command = args.remove(0);
-                                            shell = Some(tty::Shell::new(command, args));
+                                    let (env, unset) = profile.environment();
+                                    let mut args =
+                                        shlex::split(&profile.command).unwrap_or_default();
+                                    // Run the shell of the user explicitly to pass arguments
+                                    if args.is_empty() && (profile.login_shell || !unset.is_empty())
+                                    {
+                                        args.push(
+                                            env::var("SHELL")
+                                                .unwrap_or_else(|_| "/bin/sh".to_string()),
+                                        );
+                                        if profile.login_shell {
+                                            args.push("-l".to_string());
+                                        }
+                                    }
+                                    if !unset.is_empty() {
+                                        // The PTY can only set variables, unset them using env
+                                        let mut env_args = vec!["env".to_string()];
+                                        for name in unset {
+                                            env_args.push("-u".to_string());
+                                            env_args.push(name);
                                         }
+                                        env_args.append(&mut args);
+                                        args = env_args;
+                                    }
+                                    let mut shell = None;
+                                    if !args.is_empty() {
+                                        let command = args.remove(0);
+                                        shell = Some(tty::Shell::new(command, args));
                                     }
                                     let working_directory = (!profile.working_directory.is_empty())
                                         .then(|| profile.working_directory.clone().into());
@@ -1220,8 +2064,8 @@ impl App {
                                     let options = tty::Options {
                                         shell,
                                         working_directory,
-                                        hold: profile.hold,
-                                        env: HashMap::new(),
+                                        hold: profile.close_on_exit != CloseOnExit::Always,
+                                        env,
                                     };
                                     let tab_title_override = if profile.tab_title.is_empty() {
                                         None
@@ -1237,6 +2081,7 @@ impl App {
                                 .text(
                                     tab_title_override
                                         .clone()
+                                        .or_else(|| container_opt.clone())
                                         .unwrap_or_else(|| fl!("new-terminal")),
                                 )
                                 .closable()
@@ -1249,11 +2094,20 @@ impl App {
                                 self.term_config.clone(),
                                 options,
                                 &self.config,
-                                *colors,
+                                *theme,
                                 profile_id_opt,
                                 tab_title_override,
                             ) {
                                 Ok(mut terminal) => {
+                                    if let Some(close_on_exit) = close_on_exit_opt {
+                                        terminal.close_on_exit = close_on_exit;
+                                    }
+                                    terminal.container_opt = container_opt;
+                                    terminal.set_initial_input(initial_input);
+                                    if terminal.initial_input_pending() {
+                                        initial_input_command =
+                                            initial_input_timeout(current_pane, entity);
+                                    }
                                     terminal.set_config(&self.config, &self.themes, self.zoom_adj);
                                     tab_model
                                         .data_set::<Mutex<Terminal>>(entity, Mutex::new(terminal));
@@ -1281,7 +2135,7 @@ impl App {
                 log::warn!("tried to create new tab before having event channel");
             }
         }
-        self.update_title(Some(pane))
+        Command::batch([self.update_title(Some(pane)), initial_input_command])
     }
 }
 
@@ -1322,6 +2176,18 @@ impl Application for App {
         }
 
         let app_themes = vec![fl!("match-desktop"), fl!("dark"), fl!("light")];
+        let background_image_modes = vec![fl!("scaled"), fl!("tiled"), fl!("centered")];
+        let close_on_exit_names = vec![
+            fl!("never-close"),
+            fl!("always-close"),
+            fl!("close-on-success"),
+        ];
+        let color_scheme_modes = vec![
+            fl!("match-app-theme"),
+            fl!("match-desktop"),
+            fl!("always-dark"),
+            fl!("always-light"),
+        ];
 
         let font_name_faces_map = {
             let mut font_name_faces_map = BTreeMap::<_, Vec<_>>::new();
@@ -1357,7 +2223,7 @@ impl Application for App {
             });
             font_name_faces_map
         };
-        let font_names = font_name_faces_map.keys().cloned().collect();
+        let font_names: Vec<String> = font_name_faces_map.keys().cloned().collect();
 
         let mut font_size_names = Vec::new();
         let mut font_sizes = Vec::new();
@@ -1366,6 +2232,33 @@ impl Application for App {
             font_sizes.push(font_size);
         }
 
+        // Profile font settings, the first entry uses the default setting
+        let mut profile_font_names = vec![fl!("default")];
+        profile_font_names.extend(font_names.iter().cloned());
+        let mut profile_font_size_names = vec![fl!("default")];
+        profile_font_size_names.extend(font_size_names.iter().cloned());
+        let mut line_height_names = vec![fl!("default")];
+        let mut line_heights = vec![None];
+        for line_height in [100, 120, 140, 160, 180, 200] {
+            line_height_names.push(format!("{}", f32::from(line_height) / 100.0));
+            line_heights.push(Some(line_height));
+        }
+        let mut padding_names = vec![fl!("default")];
+        let mut paddings = vec![None];
+        for padding in [0, 4, 8, 12, 16, 24, 32] {
+            padding_names.push(format!("{padding}px"));
+            paddings.push(Some(padding));
+        }
+        let mut scrolling_history_names = Vec::new();
+        let mut scrolling_histories = Vec::new();
+        for lines in [1_000, 10_000, 100_000, MAX_SCROLLING_HISTORY] {
+            scrolling_history_names.push(fl!("scrollback-lines", lines = lines));
+            scrolling_histories.push(lines);
+        }
+        // Profile scrollback settings, the first entry uses the default setting
+        let mut profile_scrolling_history_names = vec![fl!("default")];
+        profile_scrolling_history_names.extend(scrolling_history_names.iter().cloned());
+
         let mut all_font_weights_vals_names_map = BTreeMap::new();
 
         macro_rules! populate_font_weights {
@@ -1409,6 +2302,14 @@ impl Application for App {
             zoom_steps.push(zoom_step);
         }
 
+        // WCAG AA for large text, AA and AAA
+        let mut minimum_contrast_names = vec![fl!("off")];
+        let mut minimum_contrasts = vec![None];
+        for minimum_contrast in [30, 45, 70] {
+            minimum_contrast_names.push(format!("{}:1", f32::from(minimum_contrast) / 10.0));
+            minimum_contrasts.push(Some(minimum_contrast));
+        }
+
         let pane_model = TerminalPaneGrid::new(segmented_button::ModelBuilder::default().build());
         let mut terminal_ids = HashMap::new();
         terminal_ids.insert(pane_model.focus, widget::Id::unique());
@@ -1420,9 +2321,22 @@ impl Application for App {
             config: flags.config,
             key_binds: key_binds(),
             app_themes,
+            background_image: None,
+            background_image_modes,
+            close_on_exit_names,
+            color_scheme_modes,
             font_names,
             font_size_names,
             font_sizes,
+            profile_font_names,
+            profile_font_size_names,
+            line_height_names,
+            line_heights,
+            profile_scrolling_history_names,
+            scrolling_history_names,
+            scrolling_histories,
+            padding_names,
+            paddings,
             font_name_faces_map,
             all_font_weights_vals_names_map,
             all_font_stretches_vals_names_map,
@@ -1433,30 +2347,51 @@ impl Application for App {
             zoom_adj: 0,
             zoom_step_names,
             zoom_steps,
+            minimum_contrast_names,
+            minimum_contrasts,
             theme_names_dark: Vec::new(),
             theme_names_light: Vec::new(),
+            theme_labels_dark: Vec::new(),
+            theme_labels_light: Vec::new(),
             themes: HashMap::new(),
+            builtin_themes: terminal_theme::terminal_themes(),
+            dir_themes: HashMap::new(),
             context_page: ContextPage::Settings,
             dialog_opt: None,
             terminal_ids,
             find: false,
+            find_forwards: false,
             find_search_id: widget::Id::unique(),
             find_search_value: String::new(),
             startup_options: flags.startup_options,
             term_config: flags.term_config,
             term_event_tx_opt: None,
+            color_scheme_editor_opt: None,
+            color_scheme_dir_errors: Vec::new(),
             color_scheme_errors: Vec::new(),
             color_scheme_expanded: None,
             color_scheme_renaming: None,
             color_scheme_rename_id: widget::Id::unique(),
             color_scheme_tab_model: widget::segmented_button::Model::default(),
+            profile_errors: Vec::new(),
             profile_expanded: None,
+            profile_env_unset_values: BTreeMap::new(),
+            profile_launcher: false,
+            profile_launcher_search: String::new(),
+            profile_launcher_search_id: widget::Id::unique(),
             show_advanced_font_settings: false,
             modifiers: Modifiers::empty(),
+            ssh_hosts: ssh_config::ssh_hosts(),
+            containers: Vec::new(),
         };
 
+        app.dir_themes = terminal_theme::color_scheme_dir_themes(&mut app.color_scheme_dir_errors);
         app.set_curr_font_weights_and_stretches();
-        let command = Command::batch([app.update_config(), app.update_title(None)]);
+        let command = Command::batch([
+            app.update_config(),
+            app.update_title(None),
+            app.update_containers(),
+        ]);
 
         (app, command)
     }
@@ -1468,8 +2403,7 @@ impl Application for App {
             self.core.window.show_context = false;
         } else if self.find {
             // Close find if open
-            self.find = false;
-            self.find_search_value.clear();
+            return self.update(Message::Find(false));
         }
 
         // Focus correct widget
@@ -1513,17 +2447,60 @@ impl Application for App {
                 self.config.app_theme = app_theme;
                 return self.save_config();
             }
+            Message::BackgroundImage(background_image) => {
+                config_set!(background_image, background_image);
+                return self.update_background_image();
+            }
+            Message::BackgroundImageDim(background_image_dim) => {
+                config_set!(background_image_dim, cmp::min(100, background_image_dim));
+            }
+            Message::BackgroundImageLoaded(path, handle_opt) => {
+                // Ignore results for images that are no longer selected
+                if let Some((current_path, current_handle)) = &mut self.background_image {
+                    if *current_path == path {
+                        *current_handle = handle_opt;
+                    }
+                }
+            }
+            Message::BackgroundImageMode(background_image_mode) => {
+                config_set!(background_image_mode, background_image_mode);
+            }
+            Message::BackgroundImageOpen => {
+                if self.dialog_opt.is_none() {
+                    let (dialog, command) = Dialog::new(
+                        DialogKind::OpenFile,
+                        None,
+                        Message::DialogMessage,
+                        Message::BackgroundImageResult,
+                    );
+                    self.dialog_opt = Some(dialog);
+                    return command;
+                }
+            }
+            Message::BackgroundImageResult(result) => {
+                self.dialog_opt = None;
+                if let DialogResult::Open(mut paths) = result {
+                    if !paths.is_empty() {
+                        return self.update(Message::BackgroundImage(Some(paths.remove(0))));
+                    }
+                }
+            }
             Message::ColorSchemeCollapse => {
                 self.color_scheme_expanded = None;
             }
             Message::ColorSchemeDelete(color_scheme_kind, color_scheme_id) => {
                 self.color_scheme_expanded = None;
+                if self.color_scheme_editor_opt.as_ref().is_some_and(|editor| {
+                    editor.kind == color_scheme_kind && editor.id == color_scheme_id
+                }) {
+                    self.color_scheme_editor_opt = None;
+                }
                 self.config
                     .color_schemes_mut(color_scheme_kind)
                     .remove(&color_scheme_id);
                 return self.save_color_schemes(color_scheme_kind);
             }
-            Message::ColorSchemeExport(color_scheme_kind, color_scheme_id_opt) => {
+            Message::ColorSchemeExport(color_scheme_kind, color_scheme_id_opt, format) => {
                 self.color_scheme_expanded = None;
                 if let Some(color_scheme_name) = match color_scheme_id_opt {
                     Some(color_scheme_id) => self
@@ -1536,7 +2513,7 @@ impl Application for App {
                     if self.dialog_opt.is_none() {
                         let (dialog, command) = Dialog::new(
                             DialogKind::SaveFile {
-                                filename: format!("{}.ron", color_scheme_name),
+                                filename: format!("{}.{}", color_scheme_name, format.extension()),
                             },
                             None,
                             Message::DialogMessage,
@@ -1544,6 +2521,7 @@ impl Application for App {
                                 Message::ColorSchemeExportResult(
                                     color_scheme_kind,
                                     color_scheme_id_opt,
+                                    format,
                                     result,
                                 )
                             },
@@ -1553,47 +2531,36 @@ impl Application for App {
                     }
                 }
             }
-            Message::ColorSchemeExportResult(color_scheme_kind, color_scheme_id_opt, result) => {
+            Message::ColorSchemeExportResult(
+                color_scheme_kind,
+                color_scheme_id_opt,
+                format,
+                result,
+            ) => {
                 //TODO: show errors in UI
                 self.dialog_opt = None;
                 if let DialogResult::Open(paths) = result {
                     let path = &paths[0];
-                    match color_scheme_id_opt {
+                    let color_scheme = match color_scheme_id_opt {
                         Some(color_scheme_id) => {
-                            if let Some(color_scheme) = self
+                            match self
                                 .config
                                 .color_schemes(color_scheme_kind)
                                 .get(&color_scheme_id)
                             {
-                                match ron::ser::to_string_pretty(
-                                    &color_scheme,
-                                    ron::ser::PrettyConfig::new(),
-                                ) {
-                                    Ok(ron) => {
-                                        if let Err(err) = fs::write(path, ron) {
-                                            log::error!(
-                                                "failed to export {:?} to {:?}: {}",
-                                                color_scheme_id,
-                                                path,
-                                                err
-                                            );
-                                        }
-                                    }
-                                    Err(err) => {
-                                        log::error!(
-                                            "failed to serialize color scheme {:?}: {}",
-                                            color_scheme_id,
-                                            err
-                                        );
-                                    }
+                                Some(color_scheme) => color_scheme.clone(),
+                                None => {
+                                    log::error!(
+                                        "failed to find color scheme {:?}",
+                                        color_scheme_id
+                                    );
+                                    return Command::none();
                                 }
-                            } else {
-                                log::error!("failed to find color scheme {:?}", color_scheme_id);
                             }
                         }
                         None => {
                             let name = format!("COSMIC {:?}", color_scheme_kind);
-                            let color_scheme = match color_scheme_kind {
+                            match color_scheme_kind {
                                 ColorSchemeKind::Dark => ColorScheme::from((
                                     name.as_str(),
                                     &terminal_theme::cosmic_dark(),
@@ -1602,34 +2569,88 @@ impl Application for App {
                                     name.as_str(),
                                     &terminal_theme::cosmic_light(),
                                 )),
-                            };
-                            //TODO: do not duplicate code
-                            match ron::ser::to_string_pretty(
-                                &color_scheme,
-                                ron::ser::PrettyConfig::new(),
-                            ) {
-                                Ok(ron) => {
-                                    if let Err(err) = fs::write(path, ron) {
-                                        log::error!(
-                                            "failed to export {:?} to {:?}: {}",
-                                            color_scheme.name,
-                                            path,
-                                            err
-                                        );
-                                    }
-                                }
-                                Err(err) => {
-                                    log::error!(
-                                        "failed to serialize color scheme {:?}: {}",
-                                        color_scheme.name,
-                                        err
-                                    );
-                                }
                             }
                         }
+                    };
+
+                    // The extension chosen in the dialog overrides the requested format
+                    let format = ColorSchemeFormat::detect(path, "")
+                        .filter(|format| ColorSchemeFormat::EXPORT.contains(format))
+                        .unwrap_or(format);
+                    match format.export(&color_scheme) {
+                        Ok(exported) => {
+                            if let Err(err) = fs::write(path, exported) {
+                                log::error!(
+                                    "failed to export {:?} to {:?}: {}",
+                                    color_scheme.name,
+                                    path,
+                                    err
+                                );
+                            }
+                        }
+                        Err(err) => {
+                            log::error!(
+                                "failed to serialize color scheme {:?}: {}",
+                                color_scheme.name,
+                                err
+                            );
+                        }
                     }
                 }
             }
+            Message::ColorSchemeDirChanged => {
+                log::info!("reload color scheme directories");
+                self.color_scheme_dir_errors.clear();
+                self.dir_themes =
+                    terminal_theme::color_scheme_dir_themes(&mut self.color_scheme_dir_errors);
+                return self.update_config();
+            }
+            Message::ColorSchemeEdit(color_scheme_kind, color_scheme_id) => {
+                self.color_scheme_expanded = None;
+                let command = self.close_color_scheme_editor();
+                if let Some(color_scheme) = self
+                    .config
+                    .color_schemes(color_scheme_kind)
+                    .get(&color_scheme_id)
+                {
+                    self.color_scheme_editor_opt = Some(ColorSchemeEditor::new(
+                        color_scheme_kind,
+                        color_scheme_id,
+                        color_scheme,
+                    ));
+                }
+                return command;
+            }
+            Message::ColorSchemeEditClose => {
+                return self.close_color_scheme_editor();
+            }
+            Message::ColorSchemeEditDeriveBright(derive) => {
+                return self.update_color_scheme_editor(true, |_editor, color_scheme| {
+                    ColorSchemeEditor::set_derive_brights(color_scheme, derive);
+                });
+            }
+            Message::ColorSchemeEditDeriveDim(derive) => {
+                return self.update_color_scheme_editor(true, |_editor, color_scheme| {
+                    ColorSchemeEditor::set_derive_dims(color_scheme, derive);
+                });
+            }
+            Message::ColorSchemeEditPicker(update) => {
+                // Dragging only previews the color, it is saved when the drag is finished
+                let save = !matches!(update, ColorPickerUpdate::ActiveColor(_));
+                let mut picker_command = Command::none();
+                let command = self.update_color_scheme_editor(save, |editor, color_scheme| {
+                    picker_command = editor
+                        .picker
+                        .update::<cosmic::app::Message<Message>>(update);
+                    editor.apply(color_scheme);
+                });
+                return Command::batch([picker_command, command]);
+            }
+            Message::ColorSchemeEditSlot(slot) => {
+                return self.update_color_scheme_editor(true, |editor, color_scheme| {
+                    editor.select(slot, color_scheme);
+                });
+            }
             Message::ColorSchemeExpand(color_scheme_kind, color_scheme_id_opt) => {
                 self.color_scheme_expanded = Some((color_scheme_kind, color_scheme_id_opt));
             }
@@ -1651,30 +2672,23 @@ impl Application for App {
                 if let DialogResult::Open(paths) = result {
                     self.color_scheme_errors.clear();
                     for path in &paths {
-                        let mut file = match fs::File::open(path) {
-                            Ok(ok) => ok,
-                            Err(err) => {
-                                self.color_scheme_errors
-                                    .push(format!("Failed to open {path:?}: {err}"));
-                                continue;
-                            }
-                        };
-                        match ron::de::from_reader::<_, ColorScheme>(&mut file) {
-                            Ok(color_scheme) => {
-                                // Get next color_scheme ID
-                                let color_scheme_id = self
-                                    .config
-                                    .color_schemes(color_scheme_kind)
-                                    .last_key_value()
-                                    .map(|(id, _)| ColorSchemeId(id.0 + 1))
-                                    .unwrap_or_default();
-                                self.config
-                                    .color_schemes_mut(color_scheme_kind)
-                                    .insert(color_scheme_id, color_scheme);
+                        match color_scheme_format::import(path) {
+                            Ok(color_schemes) => {
+                                for color_scheme in color_schemes {
+                                    // Get next color_scheme ID
+                                    let color_scheme_id = self
+                                        .config
+                                        .color_schemes(color_scheme_kind)
+                                        .last_key_value()
+                                        .map(|(id, _)| ColorSchemeId(id.0 + 1))
+                                        .unwrap_or_default();
+                                    self.config
+                                        .color_schemes_mut(color_scheme_kind)
+                                        .insert(color_scheme_id, color_scheme);
+                                }
                             }
                             Err(err) => {
-                                self.color_scheme_errors
-                                    .push(format!("Failed to parse {path:?}: {err}"));
+                                self.color_scheme_errors.push(err);
                             }
                         }
                     }
@@ -1722,6 +2736,26 @@ impl Application for App {
                     return self.update_config();
                 }
             }
+            Message::ContainerOpen(container_id) => {
+                // Look up the container by ID, as the list may have been replaced since
+                if let Some(container) = self
+                    .containers
+                    .iter()
+                    .find(|container| container.id() == container_id)
+                {
+                    let profile = container.profile();
+                    let name = container.name.clone();
+                    return self.create_and_focus_new_terminal_with_profile(
+                        self.pane_model.focus,
+                        None,
+                        Some(profile),
+                        Some(name),
+                    );
+                }
+            }
+            Message::Containers(containers) => {
+                self.containers = containers;
+            }
             Message::Copy(entity_opt) => {
                 if let Some(tab_model) = self.pane_model.active() {
                     let entity = entity_opt.unwrap_or_else(|| tab_model.active());
@@ -1737,6 +2771,18 @@ impl Application for App {
                 }
                 return self.update_focus();
             }
+            Message::CopyMode(entity_opt) => {
+                if let Some(tab_model) = self.pane_model.active() {
+                    let entity = entity_opt.unwrap_or_else(|| tab_model.active());
+                    if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                        let mut terminal = terminal.lock().unwrap();
+                        terminal.toggle_copy_mode();
+                    }
+                } else {
+                    log::warn!("Failed to get focused pane");
+                }
+                return self.update_focus();
+            }
             Message::CopyOrSigint(entity_opt) => {
                 if let Some(tab_model) = self.pane_model.active() {
                     let entity = entity_opt.unwrap_or_else(|| tab_model.active());
@@ -1876,6 +2922,10 @@ impl Application for App {
                         let entity = tab_model.active();
                         if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
                             let terminal = terminal.lock().unwrap();
+                            if !terminal.is_copy_mode() {
+                                // Search direction is only chosen by copy mode
+                                self.find_forwards = false;
+                            }
                             let term = terminal.term.lock();
                             if let Some(text) = term.selection_to_string() {
                                 self.find_search_value = text;
@@ -1886,6 +2936,15 @@ impl Application for App {
                     }
                 } else {
                     self.find_search_value.clear();
+
+                    // Stop highlighting search matches
+                    if let Some(tab_model) = self.pane_model.active() {
+                        let entity = tab_model.active();
+                        if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                            let mut terminal = terminal.lock().unwrap();
+                            terminal.search_end();
+                        }
+                    }
                 }
 
                 // Focus correct input
@@ -1898,6 +2957,10 @@ impl Application for App {
                         if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
                             let mut terminal = terminal.lock().unwrap();
                             terminal.search(&self.find_search_value, true);
+                            if terminal.is_copy_mode() {
+                                // Return to copy mode keys so n and N repeat the search
+                                self.find = false;
+                            }
                         }
                     }
                 }
@@ -1912,6 +2975,10 @@ impl Application for App {
                         if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
                             let mut terminal = terminal.lock().unwrap();
                             terminal.search(&self.find_search_value, false);
+                            if terminal.is_copy_mode() {
+                                // Return to copy mode keys so n and N repeat the search
+                                self.find = false;
+                            }
                         }
                     }
                 }
@@ -1935,18 +3002,79 @@ impl Application for App {
             Message::FocusFollowMouse(focus_follow_mouse) => {
                 config_set!(focus_follow_mouse, focus_follow_mouse);
             }
+            Message::InitialInputTimeout(pane, entity) => {
+                if let Some(tab_model) = self.pane_model.panes.get(pane) {
+                    if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                        let mut terminal = terminal.lock().unwrap();
+                        if terminal.initial_input_timeout() {
+                            return initial_input_timeout(pane, entity);
+                        }
+                    }
+                }
+            }
             Message::Key(modifiers, key) => {
+                if self.profile_launcher && key == Key::Named(Named::Escape) {
+                    return self.update(Message::ProfileLauncher(false));
+                }
+
                 for (key_bind, action) in &self.key_binds {
                     if key_bind.matches(modifiers, &key) {
                         return self.update(action.message(None));
                     }
                 }
+
+                if self.copy_mode_keys_enabled() {
+                    if let Some(tab_model) = self.pane_model.active() {
+                        let entity = tab_model.active();
+                        if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                            let mut terminal = terminal.lock().unwrap();
+                            if terminal.is_copy_mode() {
+                                match terminal.copy_mode.action(modifiers, &key) {
+                                    Some(CopyModeAction::Exit) => {
+                                        terminal.toggle_copy_mode();
+                                    }
+                                    Some(CopyModeAction::Search(forwards)) => {
+                                        drop(terminal);
+                                        self.find_forwards = forwards;
+                                        return self.update(Message::Find(true));
+                                    }
+                                    Some(CopyModeAction::SearchNext) => {
+                                        terminal.search_repeat(true);
+                                    }
+                                    Some(CopyModeAction::SearchPrevious) => {
+                                        terminal.search_repeat(false);
+                                    }
+                                    Some(CopyModeAction::Yank) => {
+                                        let text_opt = terminal.term.lock().selection_to_string();
+                                        terminal.toggle_copy_mode();
+                                        if let Some(text) = text_opt {
+                                            return clipboard::write(text);
+                                        }
+                                    }
+                                    Some(action) => {
+                                        terminal.copy_mode_action(action);
+                                    }
+                                    None => {}
+                                }
+                            }
+                        }
+                    }
+                }
             }
             Message::LaunchUrl(url) => {
                 if let Err(err) = open::that_detached(&url) {
                     log::warn!("failed to open {:?}: {}", url, err);
                 }
             }
+            Message::MinimumContrast(index) => match self.minimum_contrasts.get(index) {
+                Some(minimum_contrast) => {
+                    self.config.minimum_contrast_mul_10 = *minimum_contrast;
+                    return self.save_config();
+                }
+                None => {
+                    log::warn!("failed to find minimum contrast with index {}", index);
+                }
+            },
             Message::Modifiers(modifiers) => {
                 self.modifiers = modifiers;
             }
@@ -2037,12 +3165,224 @@ impl Application for App {
                     return self.save_profiles();
                 }
             }
+            Message::ProfileEnvAdd(profile_id) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    profile.env.push(ProfileEnv {
+                        name: String::new(),
+                        value: Some(String::new()),
+                    });
+                    return self.save_profiles();
+                }
+            }
+            Message::ProfileEnvName(profile_id, env_i, text) => {
+                if let Some(profile_env) = self
+                    .config
+                    .profiles
+                    .get_mut(&profile_id)
+                    .and_then(|profile| profile.env.get_mut(env_i))
+                {
+                    profile_env.name = text;
+                    return self.save_profiles();
+                }
+            }
+            Message::ProfileEnvRemove(profile_id, env_i) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    if env_i < profile.env.len() {
+                        profile.env.remove(env_i);
+                        self.profile_env_unset_values =
+                            mem::take(&mut self.profile_env_unset_values)
+                                .into_iter()
+                                .filter_map(|((id, i), value)| {
+                                    if id != profile_id || i < env_i {
+                                        Some(((id, i), value))
+                                    } else if i > env_i {
+                                        Some(((id, i - 1), value))
+                                    } else {
+                                        None
+                                    }
+                                })
+                                .collect();
+                        return self.save_profiles();
+                    }
+                }
+            }
+            Message::ProfileEnvUnset(profile_id, env_i, unset) => {
+                if let Some(profile_env) = self
+                    .config
+                    .profiles
+                    .get_mut(&profile_id)
+                    .and_then(|profile| profile.env.get_mut(env_i))
+                {
+                    if unset {
+                        if let Some(value) = profile_env.value.take() {
+                            self.profile_env_unset_values
+                                .insert((profile_id, env_i), value);
+                        }
+                    } else if profile_env.value.is_none() {
+                        profile_env.value = Some(
+                            self.profile_env_unset_values
+                                .remove(&(profile_id, env_i))
+                                .unwrap_or_default(),
+                        );
+                    }
+                    return self.save_profiles();
+                }
+            }
+            Message::ProfileEnvValue(profile_id, env_i, value) => {
+                if let Some(profile_env) = self
+                    .config
+                    .profiles
+                    .get_mut(&profile_id)
+                    .and_then(|profile| profile.env.get_mut(env_i))
+                {
+                    self.profile_env_unset_values.remove(&(profile_id, env_i));
+                    profile_env.value = Some(value);
+                    return self.save_profiles();
+                }
+            }
             Message::ProfileExpand(profile_id) => {
                 self.profile_expanded = Some(profile_id);
             }
-            Message::ProfileHold(profile_id, hold) => {
+            Message::ProfileExport(profile_id_opt) => {
+                let (profile_ids, filename) = match profile_id_opt {
+                    Some(profile_id) => match self.config.profiles.get(&profile_id) {
+                        Some(profile) => (vec![profile_id], format!("{}.ron", profile.name)),
+                        None => return Command::none(),
+                    },
+                    None => (
+                        self.config.profiles.keys().copied().collect(),
+                        format!("{}.ron", fl!("profiles")),
+                    ),
+                };
+                if self.dialog_opt.is_none() {
+                    let (dialog, command) = Dialog::new(
+                        DialogKind::SaveFile { filename },
+                        None,
+                        Message::DialogMessage,
+                        move |result| Message::ProfileExportResult(profile_ids.clone(), result),
+                    );
+                    self.dialog_opt = Some(dialog);
+                    return command;
+                }
+            }
+            Message::ProfileExportResult(profile_ids, result) => {
+                self.dialog_opt = None;
+                if let DialogResult::Open(paths) = result {
+                    let path = &paths[0];
+                    self.profile_errors.clear();
+                    match profile_file::export(&self.config, &profile_ids) {
+                        Ok(exported) => {
+                            if let Err(err) = fs::write(path, exported) {
+                                log::error!("failed to export profiles to {:?}: {}", path, err);
+                                self.profile_errors
+                                    .push(format!("Failed to write {path:?}: {err}"));
+                            }
+                        }
+                        Err(err) => {
+                            log::error!("failed to serialize profiles: {}", err);
+                            self.profile_errors.push(err);
+                        }
+                    }
+                }
+            }
+            Message::ProfileFont(profile_id, index) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    // The first entry is the default font
+                    profile.font_name = index
+                        .checked_sub(1)
+                        .and_then(|index| self.font_names.get(index))
+                        .cloned();
+                    return Command::batch([self.save_profiles(), self.update_config()]);
+                }
+            }
+            Message::ProfileFontSize(profile_id, index) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    // The first entry is the default font size
+                    profile.font_size = index
+                        .checked_sub(1)
+                        .and_then(|index| self.font_sizes.get(index))
+                        .copied();
+                    return Command::batch([self.save_profiles(), self.update_config()]);
+                }
+            }
+            Message::ProfileLineHeight(profile_id, index) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    profile.line_height_mul_100 = self.line_heights.get(index).copied().flatten();
+                    return Command::batch([self.save_profiles(), self.update_config()]);
+                }
+            }
+            Message::ProfileColorSchemeMode(profile_id, color_scheme_mode) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    profile.color_scheme_mode = color_scheme_mode;
+                    return Command::batch([self.save_profiles(), self.update_config()]);
+                }
+            }
+            Message::ProfileCloseOnExit(profile_id, close_on_exit) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    profile.close_on_exit = close_on_exit;
+                    return self.save_profiles();
+                }
+            }
+            Message::ProfileImport => {
+                if self.dialog_opt.is_none() {
+                    self.profile_errors.clear();
+                    let (dialog, command) = Dialog::new(
+                        DialogKind::OpenMultipleFiles,
+                        None,
+                        Message::DialogMessage,
+                        Message::ProfileImportResult,
+                    );
+                    self.dialog_opt = Some(dialog);
+                    return command;
+                }
+            }
+            Message::ProfileImportResult(result) => {
+                self.dialog_opt = None;
+                if let DialogResult::Open(paths) = result {
+                    self.profile_errors.clear();
+                    for path in &paths {
+                        if let Err(err) = profile_file::import(&mut self.config, path) {
+                            self.profile_errors.push(err);
+                        }
+                    }
+                    return Command::batch([
+                        self.save_color_schemes(ColorSchemeKind::Dark),
+                        self.save_color_schemes(ColorSchemeKind::Light),
+                        self.save_profiles(),
+                    ]);
+                }
+            }
+            Message::ProfileInitialInput(profile_id, text) => {
                 if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
-                    profile.hold = hold;
+                    profile.initial_input = text;
+                    return self.save_profiles();
+                }
+            }
+            Message::ProfileLauncher(profile_launcher) => {
+                self.profile_launcher = profile_launcher;
+                self.profile_launcher_search.clear();
+                if profile_launcher {
+                    // Dynamic sources may have changed
+                    return Command::batch([self.update_focus(), self.update_containers()]);
+                }
+                return self.update_focus();
+            }
+            Message::ProfileLauncherSearch(search) => {
+                self.profile_launcher_search = search;
+            }
+            Message::ProfileLauncherSelect(action) => {
+                self.profile_launcher = false;
+                self.profile_launcher_search.clear();
+                return Command::batch([self.update(action.message(None)), self.update_focus()]);
+            }
+            Message::ProfileLauncherSubmit => {
+                if let Some((_, action)) = self.profile_launcher_items().into_iter().next() {
+                    return self.update(Message::ProfileLauncherSelect(action));
+                }
+            }
+            Message::ProfileLoginShell(profile_id, login_shell) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    profile.login_shell = login_shell;
                     return self.save_profiles();
                 }
             }
@@ -2064,18 +3404,121 @@ impl Application for App {
                 self.profile_expanded = Some(profile_id);
                 return self.save_profiles();
             }
+            Message::ProfileOpacity(profile_id, opacity_opt) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    profile.opacity = opacity_opt.map(|opacity| cmp::min(100, opacity));
+                    return self.save_profiles();
+                }
+            }
+            Message::ProfilePadding(profile_id, index) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    profile.padding = self.paddings.get(index).copied().flatten();
+                    return self.save_profiles();
+                }
+            }
+            Message::ProfileScrollingHistory(profile_id, index) => {
+                if let Some(profile) = self.config.profiles.get_mut(&profile_id) {
+                    profile.scrolling_history = index
+                        .checked_sub(1)
+                        .and_then(|index| self.scrolling_histories.get(index).copied());
+                    return Command::batch([self.save_profiles(), self.update_config()]);
+                }
+            }
             Message::ProfileOpen(profile_id) => {
                 return self.create_and_focus_new_terminal(self.pane_model.focus, Some(profile_id));
             }
+            Message::SshHostOpen(host_id) => {
+                // Look up the host by ID, as the list may have been replaced since
+                if let Some(host) = self
+                    .ssh_hosts
+                    .iter()
+                    .find(|host| ssh_config::host_id(host) == host_id)
+                {
+                    let profile = ssh_config::ssh_profile(host);
+                    return self.create_and_focus_new_terminal_with_profile(
+                        self.pane_model.focus,
+                        None,
+                        Some(profile),
+                        None,
+                    );
+                }
+            }
+            Message::SshHostPin(host) => {
+                let name = ssh_config::ssh_profile(&host).name;
+                if !self
+                    .config
+                    .profiles
+                    .values()
+                    .any(|profile| profile.name == name)
+                {
+                    // Get next profile ID
+                    let profile_id = self
+                        .config
+                        .profiles
+                        .last_key_value()
+                        .map(|(id, _)| ProfileId(id.0 + 1))
+                        .unwrap_or_default();
+                    self.config
+                        .profiles
+                        .insert(profile_id, ssh_config::ssh_profile(&host));
+                    self.profile_expanded = Some(profile_id);
+                    return self.save_profiles();
+                }
+            }
+            Message::SshHostsChanged => {
+                let ssh_hosts = ssh_config::ssh_hosts();
+                if ssh_hosts != self.ssh_hosts {
+                    log::info!("reload ssh hosts");
+                    self.ssh_hosts = ssh_hosts;
+                }
+            }
+            Message::ProfileRuleAdd(profile_id) => {
+                // Get next rule ID, so that new rules are checked last
+                let rule_id = self
+                    .config
+                    .profile_rules
+                    .last_key_value()
+                    .map(|(id, _)| ProfileRuleId(id.0 + 1))
+                    .unwrap_or_default();
+                self.config.profile_rules.insert(
+                    rule_id,
+                    ProfileRule {
+                        profile_id,
+                        ..Default::default()
+                    },
+                );
+                return self.save_profile_rules();
+            }
+            Message::ProfileRuleDirectory(rule_id, text) => {
+                if let Some(rule) = self.config.profile_rules.get_mut(&rule_id) {
+                    rule.directory = text;
+                    return self.save_profile_rules();
+                }
+            }
+            Message::ProfileRuleHost(rule_id, text) => {
+                if let Some(rule) = self.config.profile_rules.get_mut(&rule_id) {
+                    rule.host = text;
+                    return self.save_profile_rules();
+                }
+            }
+            Message::ProfileRuleRemove(rule_id) => {
+                if self.config.profile_rules.remove(&rule_id).is_some() {
+                    return self.save_profile_rules();
+                }
+            }
+            Message::ProfileRuleUser(rule_id, text) => {
+                if let Some(rule) = self.config.profile_rules.get_mut(&rule_id) {
+                    rule.user = text;
+                    return self.save_profile_rules();
+                }
+            }
             Message::ProfileRemove(profile_id) => {
                 // Reset matching terminals to default profile
                 for (_pane, tab_model) in self.pane_model.panes.iter() {
                     for entity in tab_model.iter() {
                         if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
                             let mut terminal = terminal.lock().unwrap();
-                            if terminal.profile_id_opt == Some(profile_id) {
-                                terminal.profile_id_opt = None;
-                            }
+                            terminal.remove_profile(profile_id);
                         }
                     }
                 }
@@ -2083,6 +3526,13 @@ impl Application for App {
                     config_set!(default_profile, None);
                 }
                 self.config.profiles.remove(&profile_id);
+                let rules_len = self.config.profile_rules.len();
+                self.config
+                    .profile_rules
+                    .retain(|_, rule| rule.profile_id != profile_id);
+                if self.config.profile_rules.len() != rules_len {
+                    return Command::batch([self.save_profiles(), self.save_profile_rules()]);
+                }
                 return self.save_profiles();
             }
             Message::ProfileSyntaxTheme(profile_id, color_scheme_kind, theme_i) => {
@@ -2131,6 +3581,14 @@ impl Application for App {
                     return self.save_config();
                 }
             }
+            Message::ScrollingHistory(index) => {
+                if let Some(scrolling_history) = self.scrolling_histories.get(index).copied() {
+                    if scrolling_history != self.config.scrolling_history {
+                        self.config.scrolling_history = scrolling_history;
+                        return self.save_config();
+                    }
+                }
+            }
             Message::UseBrightBold(use_bright_bold) => {
                 if use_bright_bold != self.config.use_bright_bold {
                     self.config.use_bright_bold = use_bright_bold;
@@ -2141,6 +3599,8 @@ impl Application for App {
                 self.show_advanced_font_settings = show;
             }
             Message::SystemThemeChange => {
+                // Color schemes generated from the desktop themes must be generated again
+                self.builtin_themes = terminal_theme::terminal_themes();
                 return self.update_config();
             }
             Message::SyntaxTheme(color_scheme_kind, index) => {
@@ -2216,6 +3676,40 @@ impl Application for App {
 
                 return self.update_title(None);
             }
+            Message::TabRestart(entity_opt) => {
+                if let Some(tab_model) = self.pane_model.active() {
+                    let entity = entity_opt.unwrap_or_else(|| tab_model.active());
+                    if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                        let mut terminal = terminal.lock().unwrap();
+                        // Only restart processes that are known to have exited
+                        if terminal.exited {
+                            match terminal.restart() {
+                                Ok(()) => {
+                                    if terminal.initial_input_pending() {
+                                        return initial_input_timeout(
+                                            self.pane_model.focus,
+                                            entity,
+                                        );
+                                    }
+                                }
+                                Err(err) => {
+                                    log::error!("failed to restart terminal: {}", err);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Message::TabColorScheme(color_scheme_override) => {
+                if let Some(tab_model) = self.pane_model.active() {
+                    let entity = tab_model.active();
+                    if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                        let mut terminal = terminal.lock().unwrap();
+                        terminal.color_scheme_override = color_scheme_override;
+                        terminal.set_config(&self.config, &self.themes, self.zoom_adj);
+                    }
+                }
+            }
             Message::TabContextAction(entity, action) => {
                 if let Some(tab_model) = self.pane_model.active() {
                     if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
@@ -2291,6 +3785,24 @@ impl Application for App {
                     }
                 }
             }
+            Message::TabProgramColorsKeep => {
+                if let Some(tab_model) = self.pane_model.active() {
+                    let entity = tab_model.active();
+                    if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                        let mut terminal = terminal.lock().unwrap();
+                        terminal.keep_program_colors();
+                    }
+                }
+            }
+            Message::TabProgramColorsReset => {
+                if let Some(tab_model) = self.pane_model.active() {
+                    let entity = tab_model.active();
+                    if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                        let mut terminal = terminal.lock().unwrap();
+                        terminal.reset_program_colors();
+                    }
+                }
+            }
             Message::TermEvent(pane, entity, event) => {
                 match event {
                     TermEvent::Bell => {
@@ -2334,8 +3846,11 @@ impl Application for App {
                     TermEvent::CursorBlinkingChange => {
                         //TODO: should we blink the cursor?
                     }
+                    TermEvent::ChildExit(exit_status) => {
+                        return self.process_exited(pane, entity, Some(exit_status));
+                    }
                     TermEvent::Exit => {
-                        return self.update(Message::TabClose(Some(entity)));
+                        return self.process_exited(pane, entity, None);
                     }
                     TermEvent::PtyWrite(text) => {
                         if let Some(tab_model) = self.pane_model.panes.get(pane) {
@@ -2350,7 +3865,10 @@ impl Application for App {
                             let tab_title_override =
                                 if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
                                     let terminal = terminal.lock().unwrap();
-                                    terminal.tab_title_override.clone()
+                                    terminal
+                                        .tab_title_override
+                                        .clone()
+                                        .or_else(|| terminal.container_opt.clone())
                                 } else {
                                     None
                                 };
@@ -2372,20 +3890,28 @@ impl Application for App {
                     }
                     TermEvent::Title(title) => {
                         if let Some(tab_model) = self.pane_model.panes.get_mut(pane) {
-                            let has_override =
+                            let (has_override, container_opt) =
                                 if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
                                     let terminal = terminal.lock().unwrap();
-                                    terminal.tab_title_override.is_some()
+                                    (
+                                        terminal.tab_title_override.is_some(),
+                                        terminal.container_opt.clone(),
+                                    )
                                 } else {
-                                    false
+                                    (false, None)
                                 };
                             if !has_override {
-                                tab_model.text_set(entity, title);
+                                match container_opt {
+                                    Some(container) => {
+                                        tab_model.text_set(entity, format!("[{container}] {title}"))
+                                    }
+                                    None => tab_model.text_set(entity, title),
+                                };
                             }
                         }
                         return self.update_title(Some(pane));
                     }
-                    TermEvent::MouseCursorDirty | TermEvent::Wakeup => {
+                    TermEvent::MouseCursorDirty => {
                         if let Some(tab_model) = self.pane_model.panes.get(pane) {
                             if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
                                 let mut terminal = terminal.lock().unwrap();
@@ -2393,8 +3919,34 @@ impl Application for App {
                             }
                         }
                     }
-                    TermEvent::ChildExit(_error_code) => {
-                        //Ignore this for now
+                    TermEvent::Wakeup => {
+                        let mut exited = false;
+                        let mut title_changed = false;
+                        if let Some(tab_model) = self.pane_model.panes.get(pane) {
+                            if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                                let mut terminal = terminal.lock().unwrap();
+                                terminal.needs_update = true;
+                                exited = terminal.take_exited();
+                                terminal.update_initial_input();
+                                // Switch profiles when the shell reported a new location
+                                let tab_title_override = terminal.tab_title_override.clone();
+                                if terminal.update_profile_from_rules(&self.config) {
+                                    terminal.set_config(&self.config, &self.themes, self.zoom_adj);
+                                    title_changed =
+                                        terminal.tab_title_override != tab_title_override;
+                                }
+                            }
+                        }
+                        if exited {
+                            return self.process_exited(pane, entity, None);
+                        }
+                        if title_changed {
+                            return self.update(Message::TermEvent(
+                                pane,
+                                entity,
+                                TermEvent::ResetTitle,
+                            ));
+                        }
                     }
                 }
             }
@@ -2428,6 +3980,9 @@ impl Application for App {
                 return self.update(Message::TabNew);
             }
             Message::ToggleContextPage(context_page) => {
+                // The color scheme editor does not stay open when the context page changes
+                let command = self.close_color_scheme_editor();
+
                 if self.context_page == context_page {
                     self.core.window.show_context = !self.core.window.show_context;
                 } else {
@@ -2461,6 +4016,12 @@ impl Application for App {
                 }
 
                 self.set_context_title(context_page.title());
+
+                // Containers may have been created since they were last discovered
+                if self.context_page == ContextPage::Profiles && self.core.window.show_context {
+                    return Command::batch([command, self.update_containers()]);
+                }
+                return command;
             }
             Message::UpdateDefaultProfile((default, profile_id)) => {
                 config_set!(default_profile, default.then_some(profile_id));
@@ -2506,20 +4067,49 @@ impl Application for App {
             ContextPage::ColorSchemes(color_scheme_kind) => self.color_schemes(color_scheme_kind),
             ContextPage::Profiles => self.profiles(),
             ContextPage::Settings => self.settings(),
+            ContextPage::TabColorScheme => self.tab_color_scheme(),
         })
     }
 
     fn header_start(&self) -> Vec<Element<Self::Message>> {
-        vec![menu_bar(&self.config, &self.key_binds)]
+        vec![menu_bar(
+            &self.config,
+            &self.key_binds,
+            &self.ssh_hosts,
+            &self.containers,
+        )]
     }
 
     fn header_end(&self) -> Vec<Element<Self::Message>> {
         let cosmic_theme::Spacing { space_xxs, .. } = self.core().system_theme().cosmic().spacing;
-        vec![widget::button(icon_cache_get("list-add-symbolic", 16))
-            .on_press(Message::TabNew)
-            .padding(space_xxs)
-            .style(style::Button::Icon)
-            .into()]
+        let new_tab = widget::row::with_children(vec![
+            widget::tooltip(
+                widget::button(icon_cache_get("list-add-symbolic", 16))
+                    .on_press(Message::TabNew)
+                    .padding(space_xxs)
+                    .style(style::Button::Icon),
+                fl!("new-tab"),
+                widget::tooltip::Position::Bottom,
+            )
+            .into(),
+            widget::tooltip(
+                widget::button(icon_cache_get("pan-down-symbolic", 16))
+                    .on_press(Message::ProfileLauncher(!self.profile_launcher))
+                    .padding(space_xxs)
+                    .style(style::Button::Icon),
+                fl!("open-profile"),
+                widget::tooltip::Position::Bottom,
+            )
+            .into(),
+        ]);
+        if self.profile_launcher {
+            vec![widget::popover(new_tab)
+                .popup(self.profile_launcher())
+                .position(widget::popover::Position::Bottom)
+                .into()]
+        } else {
+            vec![new_tab.into()]
+        }
     }
 
     fn view_window(&self, window_id: window::Id) -> Element<Message> {
@@ -2558,24 +4148,36 @@ impl Application for App {
                 .cloned()
                 .unwrap_or_else(widget::Id::unique);
             if let Some(terminal) = tab_model.data::<Mutex<Terminal>>(entity) {
+                let (context_menu, profile_id_opt, exited_opt) = {
+                    let terminal = terminal.lock().unwrap();
+                    (
+                        terminal.context_menu,
+                        terminal.profile_id_opt,
+                        terminal.exited.then_some(terminal.exit_status),
+                    )
+                };
+
                 let mut terminal_box = terminal_box(terminal)
                     .id(terminal_id)
                     .on_context_menu(move |position_opt| {
                         Message::TabContextMenu(pane, position_opt)
                     })
                     .on_middle_click(move || Message::MiddleClick(pane, Some(entity_middle_click)))
-                    .opacity(self.config.opacity_ratio())
-                    .padding(space_xxs);
+                    .opacity(self.config.opacity_ratio(profile_id_opt))
+                    .padding(self.config.padding(profile_id_opt).unwrap_or(space_xxs));
+
+                if let Some((_, Some(handle))) = &self.background_image {
+                    terminal_box = terminal_box.background_image(
+                        handle.clone(),
+                        self.config.background_image_mode,
+                        self.config.background_image_dim_ratio(),
+                    );
+                }
 
                 if self.config.focus_follow_mouse {
                     terminal_box = terminal_box.on_mouse_enter(move || Message::MouseEnter(pane));
                 }
 
-                let context_menu = {
-                    let terminal = terminal.lock().unwrap();
-                    terminal.context_menu
-                };
-
                 let tab_element: Element<'_, Message> = match context_menu {
                     Some(point) => widget::popover(terminal_box.context_menu(point))
                         .popup(menu::context_menu(&self.config, &self.key_binds, entity))
@@ -2584,6 +4186,33 @@ impl Application for App {
                     None => terminal_box.into(),
                 };
                 tab_column = tab_column.push(tab_element);
+
+                // Offer to restart processes that exited while the tab is kept open
+                if let Some(exit_status_opt) = exited_opt {
+                    let exited_widget = widget::row::with_children(vec![
+                        widget::text(match exit_status_opt {
+                            Some(exit_status) => {
+                                fl!("process-exited-status", status = exit_status)
+                            }
+                            None => fl!("process-exited"),
+                        })
+                        .into(),
+                        widget::horizontal_space(Length::Fill).into(),
+                        widget::button::standard(fl!("restart"))
+                            .on_press(Message::TabRestart(Some(entity)))
+                            .into(),
+                        widget::button::standard(fl!("close"))
+                            .on_press(Message::TabClose(Some(entity)))
+                            .into(),
+                    ])
+                    .align_items(Alignment::Center)
+                    .padding(space_xxs)
+                    .spacing(space_xxs);
+
+                    tab_column = tab_column.push(
+                        widget::layer_container(exited_widget).layer(cosmic_theme::Layer::Primary),
+                    );
+                }
             }
 
             //Only draw find in the currently focused pane
@@ -2595,12 +4224,14 @@ impl Application for App {
                 .id(self.find_search_id.clone())
                 .on_input(Message::FindSearchValueChanged)
                 // This is inverted for ease of use, usually in terminals you want to search
-                // upwards, which is FindPrevious
-                .on_submit(if self.modifiers.contains(Modifiers::SHIFT) {
-                    Message::FindNext
-                } else {
-                    Message::FindPrevious
-                })
+                // upwards, which is FindPrevious. Copy mode can search forwards instead.
+                .on_submit(
+                    if self.modifiers.contains(Modifiers::SHIFT) != self.find_forwards {
+                        Message::FindNext
+                    } else {
+                        Message::FindPrevious
+                    },
+                )
                 .width(Length::Fixed(320.0))
                 .trailing_icon(
                     button(icon_cache_get("edit-clear-symbolic", 16))
@@ -2645,6 +4276,17 @@ impl Application for App {
                 // TODO
             }
 
+            // Without the header bar, show the profile launcher in the focused pane
+            if self.profile_launcher && !self.config.show_headerbar && pane == self.pane_model.focus
+            {
+                tab_column = tab_column.push(
+                    widget::container(self.profile_launcher())
+                        .center_x()
+                        .padding(space_xxs)
+                        .width(Length::Fill),
+                );
+            }
+
             pane_grid::Content::new(tab_column)
         })
         .width(Length::Fill)
@@ -2658,9 +4300,12 @@ impl Application for App {
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
+        struct ColorSchemeDirSubscription;
         struct ConfigSubscription;
+        struct SshConfigSubscription;
         struct TerminalEventSubscription;
-        struct ThemeSubscription;
+        struct ThemeDarkSubscription;
+        struct ThemeLightSubscription;
         struct ThemeModeSubscription;
 
         Subscription::batch([
@@ -2693,6 +4338,134 @@ impl Application for App {
                     panic!("terminal event channel closed");
                 },
             ),
+            subscription::channel(
+                TypeId::of::<ColorSchemeDirSubscription>(),
+                1,
+                |mut output| async move {
+                    let dirs = terminal_theme::color_scheme_dirs();
+                    // Create the user directory so it can be watched
+                    if let Some(dir) = dirs.first() {
+                        if let Err(err) = fs::create_dir_all(dir) {
+                            log::warn!("failed to create {:?}: {}", dir, err);
+                        }
+                    }
+
+                    let (event_tx, mut event_rx) = mpsc::channel(1);
+                    let watcher_res = {
+                        let dirs = dirs.clone();
+                        notify::recommended_watcher(
+                            move |event_res: Result<notify::Event, notify::Error>| match event_res {
+                                Ok(event) => {
+                                    // Parents of missing directories are watched too, so only
+                                    // events on the way to a color scheme directory are used
+                                    if !event.kind.is_access()
+                                        && event.paths.iter().any(|path| {
+                                            dirs.iter().any(|dir| {
+                                                path.starts_with(dir) || dir.starts_with(path)
+                                            })
+                                        })
+                                    {
+                                        // Ignore full channel, a reload is already pending
+                                        let _ = event_tx.try_send(());
+                                    }
+                                }
+                                Err(err) => {
+                                    log::warn!(
+                                        "failed to watch color scheme directories: {:?}",
+                                        err
+                                    );
+                                }
+                            },
+                        )
+                    };
+
+                    // The watcher must be kept alive for events to be received
+                    let mut watcher_opt = match watcher_res {
+                        Ok(watcher) => Some(watcher),
+                        Err(err) => {
+                            log::warn!(
+                                "failed to create color scheme directory watcher: {:?}",
+                                err
+                            );
+                            None
+                        }
+                    };
+
+                    let mut watched = BTreeSet::new();
+                    loop {
+                        // Watch newly created directories, or the closest parent of missing ones
+                        if let Some(watcher) = &mut watcher_opt {
+                            // Removed directories lose their watch and must be watched again
+                            watched.retain(|path: &PathBuf| path.is_dir());
+                            for path in terminal_theme::color_scheme_watch_paths(&dirs) {
+                                if watched.contains(&path) {
+                                    continue;
+                                }
+                                match watcher.watch(&path, notify::RecursiveMode::NonRecursive) {
+                                    Ok(()) => {
+                                        watched.insert(path);
+                                    }
+                                    Err(err) => {
+                                        log::warn!("failed to watch {:?}: {:?}", path, err);
+                                    }
+                                }
+                            }
+                        }
+
+                        if event_rx.recv().await.is_none() {
+                            break;
+                        }
+                        output.send(Message::ColorSchemeDirChanged).await.unwrap();
+                    }
+
+                    // Watcher failed, wait forever
+                    iced::futures::future::pending().await
+                },
+            ),
+            subscription::channel(
+                TypeId::of::<SshConfigSubscription>(),
+                1,
+                |mut output| async move {
+                    let (event_tx, mut event_rx) = mpsc::channel(1);
+                    let watcher_res = notify::recommended_watcher(
+                        move |event_res: Result<notify::Event, notify::Error>| match event_res {
+                            Ok(event) => {
+                                if !event.kind.is_access() {
+                                    // Ignore full channel, a reload is already pending
+                                    let _ = event_tx.try_send(());
+                                }
+                            }
+                            Err(err) => {
+                                log::warn!("failed to watch ssh config: {:?}", err);
+                            }
+                        },
+                    );
+
+                    // The watcher must be kept alive for events to be received
+                    let _watcher_opt = match (watcher_res, ssh_config::ssh_dir()) {
+                        (Ok(mut watcher), Some(dir)) => {
+                            // Included files are usually in subdirectories of ~/.ssh
+                            if let Err(err) = watcher.watch(&dir, notify::RecursiveMode::Recursive)
+                            {
+                                log::info!("failed to watch {:?}: {:?}", dir, err);
+                            }
+                            Some(watcher)
+                        }
+                        (Ok(_), None) => None,
+                        (Err(err), _) => {
+                            log::warn!("failed to create ssh config watcher: {:?}", err);
+                            None
+                        }
+                    };
+
+                    while event_rx.recv().await.is_some() {
+                        output.send(Message::SshHostsChanged).await.unwrap();
+                    }
+
+                    // Watcher failed, wait forever
+                    iced::futures::future::pending().await
+                },
+            ),
             cosmic_config::config_subscription(
                 TypeId::of::<ConfigSubscription>(),
                 Self::APP_ID.into(),
@@ -2708,14 +4481,16 @@ impl Application for App {
                 }
                 Message::Config(update.config)
             }),
+            // Both themes are watched, as color schemes are generated from each of them
             cosmic_config::config_subscription::<_, cosmic_theme::Theme>(
-                TypeId::of::<ThemeSubscription>(),
-                if self.core.system_theme_mode().is_dark {
-                    cosmic_theme::DARK_THEME_ID
-                } else {
-                    cosmic_theme::LIGHT_THEME_ID
-                }
-                .into(),
+                TypeId::of::<ThemeDarkSubscription>(),
+                cosmic_theme::DARK_THEME_ID.into(),
+                cosmic_theme::Theme::VERSION,
+            )
+            .map(|_update| Message::SystemThemeChange),
+            cosmic_config::config_subscription::<_, cosmic_theme::Theme>(
+                TypeId::of::<ThemeLightSubscription>(),
+                cosmic_theme::LIGHT_THEME_ID.into(),
                 cosmic_theme::Theme::VERSION,
             )
             .map(|_update| Message::SystemThemeChange),
@@ -18,7 +18,10 @@ use cosmic::{
 };
 use std::collections::HashMap;
 
-use crate::{fl, Action, ColorSchemeId, ColorSchemeKind, Config, Message};
+use crate::{
+    container::Container, fl, ssh_config, Action, ColorSchemeFormat, ColorSchemeId,
+    ColorSchemeKind, Config, Message,
+};
 
 pub fn context_menu<'a>(
     config: &Config,
@@ -67,6 +70,7 @@ pub fn context_menu<'a>(
         menu_item(fl!("pane-toggle-maximize"), Action::PaneToggleMaximized),
         horizontal_rule(1),
         menu_item(fl!("new-tab"), Action::TabNew),
+        menu_item(fl!("menu-tab-color-scheme"), Action::TabColorScheme),
         menu_item(fl!("menu-settings"), Action::Settings),
         menu_checkbox(
             fl!("show-headerbar"),
@@ -103,17 +107,26 @@ pub fn color_scheme_menu<'a>(
     let menu_item =
         |label, message| menu_button(vec![widget::text(label).into()]).on_press(message);
 
-    let mut column = widget::column::with_capacity(if id_opt.is_some() { 3 } else { 1 });
+    let mut column = widget::column::with_capacity(
+        ColorSchemeFormat::EXPORT.len() + if id_opt.is_some() { 3 } else { 0 },
+    );
     if let Some(id) = id_opt {
+        column = column.push(menu_item(fl!("edit"), Message::ColorSchemeEdit(kind, id)));
         column = column.push(menu_item(
             fl!("rename"),
             Message::ColorSchemeRename(kind, id, name.to_string()),
         ));
     }
-    column = column.push(menu_item(
-        fl!("export"),
-        Message::ColorSchemeExport(kind, id_opt),
-    ));
+    for format in ColorSchemeFormat::EXPORT {
+        let label = match format {
+            ColorSchemeFormat::Ron => fl!("export"),
+            _ => fl!("export-as", format = format.name()),
+        };
+        column = column.push(menu_item(
+            label,
+            Message::ColorSchemeExport(kind, id_opt, format),
+        ));
+    }
     if let Some(id) = id_opt {
         column = column.push(menu_item(
             fl!("delete"),
@@ -143,12 +156,157 @@ pub fn color_scheme_menu<'a>(
         .into()
 }
 
-pub fn menu_bar<'a>(config: &Config, key_binds: &HashMap<KeyBind, Action>) -> Element<'a, Message> {
-    let mut profile_items = Vec::with_capacity(config.profiles.len());
+/// Entries of the profile launcher, starting with the default profile
+pub fn launcher_items(
+    config: &Config,
+    ssh_hosts: &[String],
+    containers: &[Container],
+) -> Vec<(String, Action)> {
+    let profile_names = config.profile_names();
+    let mut items =
+        Vec::with_capacity(profile_names.len() + ssh_hosts.len() + containers.len() + 1);
+
+    // New tabs use the default profile
+    let default_name_opt = config.default_profile.and_then(|default_id| {
+        profile_names
+            .iter()
+            .find(|(_, id)| *id == default_id)
+            .map(|(name, _)| name.clone())
+    });
+    items.push((
+        default_name_opt.unwrap_or_else(|| fl!("new-terminal")),
+        Action::TabNew,
+    ));
+    for (name, id) in profile_names {
+        if config.default_profile != Some(id) {
+            items.push((name, Action::ProfileOpen(id)));
+        }
+    }
+
+    // Hosts that are pinned as profiles are already listed
+    for host in ssh_hosts {
+        let name = ssh_config::ssh_profile(host).name;
+        if !config.profiles.values().any(|profile| profile.name == name) {
+            items.push((name, Action::SshHostOpen(ssh_config::host_id(host))));
+        }
+    }
+
+    for container in containers {
+        items.push((
+            container.profile().name,
+            Action::ContainerOpen(container.id()),
+        ));
+    }
+
+    items
+}
+
+pub fn profile_launcher<'a>(
+    items: Vec<(String, Action)>,
+    key_binds: &HashMap<KeyBind, Action>,
+    search: &'a str,
+    search_id: widget::Id,
+) -> Element<'a, Message> {
+    let find_key = |action: &Action| -> String {
+        for (key_bind, key_action) in key_binds {
+            if action == key_action {
+                return key_bind.to_string();
+            }
+        }
+        String::new()
+    };
+
+    let mut list = widget::column::with_capacity(items.len().max(1));
+    if items.is_empty() {
+        list = list.push(
+            widget::container(widget::text(fl!("no-profiles-found")))
+                .padding([4, 16])
+                .width(Length::Fill),
+        );
+    }
+    for (name, action) in items {
+        let key = find_key(&action);
+        list = list.push(
+            menu_button(vec![
+                widget::text(name).into(),
+                horizontal_space(Length::Fill).into(),
+                widget::text(key).into(),
+            ])
+            .on_press(Message::ProfileLauncherSelect(action)),
+        );
+    }
+
+    widget::container(column!(
+        widget::container(
+            widget::text_input::text_input(fl!("search-profiles"), search)
+                .id(search_id)
+                .on_input(Message::ProfileLauncherSearch)
+                .on_submit(Message::ProfileLauncherSubmit)
+        )
+        .padding(4),
+        horizontal_rule(1),
+        widget::scrollable(list),
+    ))
+    .padding(1)
+    //TODO: move style to libcosmic
+    .style(theme::Container::custom(|theme| {
+        let cosmic = theme.cosmic();
+        let component = &cosmic.background.component;
+        widget::container::Appearance {
+            icon_color: Some(component.on.into()),
+            text_color: Some(component.on.into()),
+            background: Some(Background::Color(component.base.into())),
+            border: Border {
+                radius: 8.0.into(),
+                width: 1.0,
+                color: component.divider.into(),
+            },
+            ..Default::default()
+        }
+    }))
+    .width(Length::Fixed(320.0))
+    .max_height(480.0)
+    .into()
+}
+
+pub fn menu_bar<'a>(
+    config: &Config,
+    key_binds: &HashMap<KeyBind, Action>,
+    ssh_hosts: &[String],
+    containers: &[Container],
+) -> Element<'a, Message> {
+    let mut profile_items =
+        Vec::with_capacity(config.profiles.len() + ssh_hosts.len() + containers.len() + 2);
     for (name, id) in config.profile_names() {
         profile_items.push(MenuItem::Button(name, Action::ProfileOpen(id)));
     }
 
+    // Hosts that are pinned as profiles are already listed
+    let mut ssh_items = Vec::with_capacity(ssh_hosts.len());
+    for host in ssh_hosts {
+        let name = ssh_config::ssh_profile(host).name;
+        if !config.profiles.values().any(|profile| profile.name == name) {
+            ssh_items.push(MenuItem::Button(
+                name,
+                Action::SshHostOpen(ssh_config::host_id(host)),
+            ));
+        }
+    }
+    if !profile_items.is_empty() && !ssh_items.is_empty() {
+        profile_items.push(MenuItem::Divider);
+    }
+    profile_items.append(&mut ssh_items);
+
+    if !profile_items.is_empty() && !containers.is_empty() {
+        profile_items.push(MenuItem::Divider);
+    }
+    for container in containers {
+        profile_items.push(MenuItem::Button(
+            container.profile().name,
+            Action::ContainerOpen(container.id()),
+        ));
+    }
+
     //TODO: what to do if there are no profiles?
 
     MenuBar::new(vec![
@@ -163,6 +321,7 @@ pub fn menu_bar<'a>(config: &Config, key_binds: &HashMap<KeyBind, Action>) -> El
                     MenuItem::Folder(fl!("profile"), profile_items),
                     MenuItem::Button(fl!("menu-profiles"), Action::Profiles),
                     MenuItem::Divider,
+                    MenuItem::Button(fl!("restart-tab"), Action::TabRestart),
                     MenuItem::Button(fl!("close-tab"), Action::TabClose),
                     MenuItem::Divider,
                     MenuItem::Button(fl!("quit"), Action::WindowClose),
@@ -179,6 +338,7 @@ pub fn menu_bar<'a>(config: &Config, key_binds: &HashMap<KeyBind, Action>) -> El
                     MenuItem::Button(fl!("select-all"), Action::SelectAll),
                     MenuItem::Divider,
                     MenuItem::Button(fl!("find"), Action::Find),
+                    MenuItem::Button(fl!("copy-mode"), Action::CopyMode),
                 ],
             ),
         ),
@@ -198,6 +358,7 @@ pub fn menu_bar<'a>(config: &Config, key_binds: &HashMap<KeyBind, Action>) -> El
                     MenuItem::Button(fl!("split-vertical"), Action::PaneSplitVertical),
                     MenuItem::Button(fl!("pane-toggle-maximize"), Action::PaneToggleMaximized),
                     MenuItem::Divider,
+                    MenuItem::Button(fl!("menu-tab-color-scheme"), Action::TabColorScheme),
                     MenuItem::Button(
                         fl!("menu-color-schemes"),
                         Action::ColorSchemes(config.color_scheme_kind()),
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::config::{ColorScheme, ColorSchemeId, ColorSchemeKind, Config, Profile, ProfileId};
+
+/// Profiles shared as a single RON file, with the custom color schemes they use
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ProfileFile {
+    pub profiles: Vec<Profile>,
+    #[serde(default)]
+    pub color_schemes_dark: Vec<ColorScheme>,
+    #[serde(default)]
+    pub color_schemes_light: Vec<ColorScheme>,
+}
+
+impl ProfileFile {
+    fn color_schemes_mut(&mut self, color_scheme_kind: ColorSchemeKind) -> &mut Vec<ColorScheme> {
+        match color_scheme_kind {
+            ColorSchemeKind::Dark => &mut self.color_schemes_dark,
+            ColorSchemeKind::Light => &mut self.color_schemes_light,
+        }
+    }
+}
+
+fn syntax_theme_mut(profile: &mut Profile, color_scheme_kind: ColorSchemeKind) -> &mut String {
+    match color_scheme_kind {
+        ColorSchemeKind::Dark => &mut profile.syntax_theme_dark,
+        ColorSchemeKind::Light => &mut profile.syntax_theme_light,
+    }
+}
+
+/// Serialize profiles, including the custom color schemes they refer to
+pub fn export(config: &Config, profile_ids: &[ProfileId]) -> Result<String, String> {
+    let mut profile_file = ProfileFile::default();
+    for profile_id in profile_ids {
+        let Some(profile) = config.profiles.get(profile_id) else {
+            return Err(format!("failed to find profile {:?}", profile_id));
+        };
+        let mut profile = profile.clone();
+
+        for color_scheme_kind in [ColorSchemeKind::Dark, ColorSchemeKind::Light] {
+            let theme_name = syntax_theme_mut(&mut profile, color_scheme_kind).clone();
+            // Custom color schemes are referred to by name, adjusted for duplicates
+            let Some(color_scheme) = config
+                .color_scheme_names(color_scheme_kind)
+                .into_iter()
+                .find(|(name, _)| name == &theme_name)
+                .and_then(|(_, id)| config.color_schemes(color_scheme_kind).get(&id))
+            else {
+                continue;
+            };
+
+            let color_schemes = profile_file.color_schemes_mut(color_scheme_kind);
+            if !color_schemes
+                .iter()
+                .any(|color_scheme| color_scheme.name == theme_name)
+            {
+                color_schemes.push(ColorScheme {
+                    name: theme_name,
+                    ..color_scheme.clone()
+                });
+            }
+        }
+
+        profile_file.profiles.push(profile);
+    }
+
+    ron::ser::to_string_pretty(&profile_file, ron::ser::PrettyConfig::new())
+        .map_err(|err| err.to_string())
+}
+
+/// Add profiles and their color schemes from a file to the config, profiles with names that
+/// already exist are renamed
+pub fn import(config: &mut Config, path: &Path) -> Result<Vec<ProfileId>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|err| format!("Failed to open {path:?}: {err}"))?;
+    let mut profile_file: ProfileFile =
+        ron::from_str(&contents).map_err(|err| format!("Failed to parse {path:?}: {err}"))?;
+
+    for color_scheme_kind in [ColorSchemeKind::Dark, ColorSchemeKind::Light] {
+        for color_scheme in profile_file.color_schemes_mut(color_scheme_kind).drain(..) {
+            let imported_name = color_scheme.name.clone();
+
+            // Reuse identical color schemes, for example when importing a file twice
+            let existing_id = config
+                .color_schemes(color_scheme_kind)
+                .iter()
+                .find(|(_, existing)| **existing == color_scheme)
+                .map(|(id, _)| *id);
+            let color_scheme_id = match existing_id {
+                Some(color_scheme_id) => color_scheme_id,
+                None => {
+                    let color_scheme_id = config
+                        .color_schemes(color_scheme_kind)
+                        .last_key_value()
+                        .map(|(id, _)| ColorSchemeId(id.0 + 1))
+                        .unwrap_or_default();
+                    config
+                        .color_schemes_mut(color_scheme_kind)
+                        .insert(color_scheme_id, color_scheme);
+                    color_scheme_id
+                }
+            };
+
+            // Names of color schemes may have been adjusted for duplicates
+            if let Some((name, _)) = config
+                .color_scheme_names(color_scheme_kind)
+                .into_iter()
+                .find(|(_, id)| *id == color_scheme_id)
+            {
+                for profile in &mut profile_file.profiles {
+                    let syntax_theme = syntax_theme_mut(profile, color_scheme_kind);
+                    if *syntax_theme == imported_name {
+                        syntax_theme.clone_from(&name);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut profile_ids = Vec::with_capacity(profile_file.profiles.len());
+    for mut profile in profile_file.profiles {
+        let name = profile.name.clone();
+        let mut copies = 1;
+        while config
+            .profiles
+            .values()
+            .any(|existing| existing.name == profile.name)
+        {
+            copies += 1;
+            profile.name = format!("{} ({})", name, copies);
+        }
+
+        // Get next profile ID
+        let profile_id = config
+            .profiles
+            .last_key_value()
+            .map(|(id, _)| ProfileId(id.0 + 1))
+            .unwrap_or_default();
+        config.profiles.insert(profile_id, profile);
+        profile_ids.push(profile_id);
+    }
+    Ok(profile_ids)
+}
@@ -0,0 +1,429 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+// Longer sequences are not locations and are ignored
+const MAX_OSC_LEN: usize = 4096;
+
+/// Location of the shell reported with OSC 7 or OSC 1337
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ShellLocation {
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub directory: Option<String>,
+}
+
+/// Shell location shared between the PTY reader and the terminal
+#[derive(Clone, Debug, Default)]
+pub struct SharedShellLocation {
+    location: Arc<Mutex<ShellLocation>>,
+    changed: Arc<AtomicBool>,
+    prompt: Arc<AtomicBool>,
+    exited: Arc<AtomicBool>,
+    output: Arc<AtomicBool>,
+    integrated: Arc<AtomicBool>,
+}
+
+impl SharedShellLocation {
+    /// Check if the shell showed a prompt since the last call
+    pub fn take_prompt(&self) -> bool {
+        self.prompt.swap(false, Ordering::SeqCst)
+    }
+
+    /// Check if the process exited since the last call, even without an exit status
+    pub fn take_exited(&self) -> bool {
+        self.exited.swap(false, Ordering::SeqCst)
+    }
+
+    /// Check if the process printed anything
+    pub fn output(&self) -> bool {
+        self.output.load(Ordering::SeqCst)
+    }
+
+    /// Check if the shell reported its location or prompt, so it will report its prompts
+    pub fn integrated(&self) -> bool {
+        self.integrated.load(Ordering::SeqCst)
+    }
+
+    /// Clear the state of the previous process when the process is restarted
+    pub fn reset(&self) {
+        self.prompt.store(false, Ordering::SeqCst);
+        self.exited.store(false, Ordering::SeqCst);
+        self.output.store(false, Ordering::SeqCst);
+        self.integrated.store(false, Ordering::SeqCst);
+    }
+
+    /// Take the shell location if it changed since the last call
+    pub fn take_changed(&self) -> Option<ShellLocation> {
+        if self.changed.swap(false, Ordering::SeqCst) {
+            Some(self.location.lock().unwrap().clone())
+        } else {
+            None
+        }
+    }
+
+    fn update<F: FnOnce(&mut ShellLocation)>(&self, f: F) {
+        let mut location = self.location.lock().unwrap();
+        let old = location.clone();
+        f(&mut location);
+        if *location != old {
+            self.changed.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Scans PTY output for OSC sequences reporting the shell location, the terminal parser
+/// ignores these
+#[derive(Debug, Default)]
+pub struct OscScanner {
+    state: State,
+    osc: Vec<u8>,
+    shared: SharedShellLocation,
+}
+
+impl OscScanner {
+    pub fn new(shared: SharedShellLocation) -> Self {
+        Self {
+            shared,
+            ..Default::default()
+        }
+    }
+
+    pub fn scan(&mut self, bytes: &[u8]) {
+        if !bytes.is_empty() {
+            self.shared.output.store(true, Ordering::SeqCst);
+        }
+        for &byte in bytes {
+            self.state = match (self.state, byte) {
+                (_, 0x1B) if self.state != State::Osc => State::Escape,
+                (State::Escape, b']') => {
+                    self.osc.clear();
+                    State::Osc
+                }
+                (State::Osc, 0x07) => {
+                    self.dispatch();
+                    State::Ground
+                }
+                (State::Osc, 0x1B) => State::OscEscape,
+                (State::Osc, _) => {
+                    if self.osc.len() < MAX_OSC_LEN {
+                        self.osc.push(byte);
+                    }
+                    State::Osc
+                }
+                (State::OscEscape, b'\\') => {
+                    self.dispatch();
+                    State::Ground
+                }
+                (State::OscEscape, b']') => {
+                    self.osc.clear();
+                    State::Osc
+                }
+                _ => State::Ground,
+            };
+        }
+    }
+
+    fn dispatch(&mut self) {
+        if self.osc.len() >= MAX_OSC_LEN {
+            return;
+        }
+        let osc = String::from_utf8_lossy(&self.osc);
+        let Some((code, value)) = osc.split_once(';') else {
+            return;
+        };
+        if matches!(code, "7" | "133" | "1337") {
+            self.shared.integrated.store(true, Ordering::SeqCst);
+        }
+        match code {
+            // file://host/path, shells report it before each prompt
+            "7" => {
+                let Some(rest) = value.strip_prefix("file://") else {
+                    return;
+                };
+                let (host, path) = match rest.find('/') {
+                    Some(i) => rest.split_at(i),
+                    None => (rest, "/"),
+                };
+                let directory = percent_decode(path);
+                self.shared.update(|location| {
+                    if !host.is_empty() {
+                        location.host = Some(host.to_string());
+                    }
+                    location.directory = Some(directory);
+                });
+                self.shared.prompt.store(true, Ordering::SeqCst);
+            }
+            // Start of the prompt with semantic prompts
+            "133" if value.starts_with('A') => {
+                self.shared.prompt.store(true, Ordering::SeqCst);
+            }
+            "1337" => {
+                if let Some(remote_host) = value.strip_prefix("RemoteHost=") {
+                    let (user, host) = match remote_host.split_once('@') {
+                        Some((user, host)) => (Some(user.to_string()), host),
+                        None => (None, remote_host),
+                    };
+                    self.shared.update(|location| {
+                        location.user = user;
+                        location.host = Some(host.to_string());
+                    });
+                } else if let Some(directory) = value.strip_prefix("CurrentDir=") {
+                    self.shared.update(|location| {
+                        location.directory = Some(directory.to_string());
+                    });
+                    self.shared.prompt.store(true, Ordering::SeqCst);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = |byte: u8| char::from(byte).to_digit(16);
+            if let (Some(high), Some(low)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                decoded.push((high * 16 + low) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(unix)]
+pub use self::unix::ShellIntegrationPty;
+
+#[cfg(unix)]
+mod unix {
+    use alacritty_terminal::{
+        event::{OnResize, WindowSize},
+        tty::{ChildEvent, EventedPty, EventedReadWrite, Pty},
+    };
+    use polling::{Event, PollMode, Poller};
+    use std::{
+        fs::File,
+        io::{self, Read},
+        sync::{atomic::Ordering, Arc},
+    };
+
+    use super::{OscScanner, SharedShellLocation};
+
+    /// Reads PTY output through a duplicate of the PTY file, scanning it for the shell location
+    pub struct ShellIntegrationReader {
+        file: File,
+        scanner: OscScanner,
+    }
+
+    impl Read for ShellIntegrationReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = self.file.read(buf)?;
+            self.scanner.scan(&buf[..count]);
+            Ok(count)
+        }
+    }
+
+    /// PTY that reports the shell location, otherwise behaving like the wrapped PTY
+    pub struct ShellIntegrationPty {
+        pty: Pty,
+        reader: ShellIntegrationReader,
+        shared: SharedShellLocation,
+    }
+
+    impl ShellIntegrationPty {
+        pub fn new(pty: Pty, shared: SharedShellLocation) -> io::Result<Self> {
+            // Duplicates share the non-blocking flag of the PTY file
+            let file = pty.file().try_clone()?;
+            Ok(Self {
+                pty,
+                reader: ShellIntegrationReader {
+                    file,
+                    scanner: OscScanner::new(shared.clone()),
+                },
+                shared,
+            })
+        }
+    }
+
+    impl EventedReadWrite for ShellIntegrationPty {
+        type Reader = ShellIntegrationReader;
+        type Writer = File;
+
+        unsafe fn register(
+            &mut self,
+            poll: &Arc<Poller>,
+            interest: Event,
+            mode: PollMode,
+        ) -> io::Result<()> {
+            self.pty.register(poll, interest, mode)
+        }
+
+        fn reregister(
+            &mut self,
+            poll: &Arc<Poller>,
+            interest: Event,
+            mode: PollMode,
+        ) -> io::Result<()> {
+            self.pty.reregister(poll, interest, mode)
+        }
+
+        fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+            self.pty.deregister(poll)
+        }
+
+        fn reader(&mut self) -> &mut Self::Reader {
+            &mut self.reader
+        }
+
+        fn writer(&mut self) -> &mut Self::Writer {
+            self.pty.writer()
+        }
+    }
+
+    impl EventedPty for ShellIntegrationPty {
+        fn next_child_event(&mut self) -> Option<ChildEvent> {
+            let event = self.pty.next_child_event();
+            // Processes killed by a signal have no exit status, so the event loop sends no exit
+            // event for them when holding the terminal open
+            if let Some(ChildEvent::Exited(_)) = event {
+                self.shared.exited.store(true, Ordering::SeqCst);
+            }
+            event
+        }
+    }
+
+    impl OnResize for ShellIntegrationPty {
+        fn on_resize(&mut self, window_size: WindowSize) {
+            self.pty.on_resize(window_size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(chunks: &[&[u8]]) -> (Option<ShellLocation>, bool) {
+        let shared = SharedShellLocation::default();
+        let mut scanner = OscScanner::new(shared.clone());
+        for chunk in chunks {
+            scanner.scan(chunk);
+        }
+        (shared.take_changed(), shared.take_prompt())
+    }
+
+    fn location(host: Option<&str>, user: Option<&str>, directory: Option<&str>) -> ShellLocation {
+        ShellLocation {
+            host: host.map(str::to_string),
+            user: user.map(str::to_string),
+            directory: directory.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn osc_7_terminators() {
+        let expected = Some(location(Some("host"), None, Some("/home/user")));
+        assert_eq!(
+            scan(&[b"\x1B]7;file://host/home/user\x07"]),
+            (expected.clone(), true)
+        );
+        assert_eq!(
+            scan(&[b"\x1B]7;file://host/home/user\x1B\\"]),
+            (expected, true)
+        );
+    }
+
+    #[test]
+    fn osc_7_split() {
+        assert_eq!(
+            scan(&[b"text\x1B", b"]7;file://ho", b"st/tmp\x1B", b"\\more"]),
+            (Some(location(Some("host"), None, Some("/tmp"))), true)
+        );
+    }
+
+    #[test]
+    fn osc_7_percent_decoding() {
+        assert_eq!(
+            scan(&[b"\x1B]7;file:///home/user/My%20Files%2fx\x07"]),
+            (
+                Some(location(None, None, Some("/home/user/My Files/x"))),
+                true
+            )
+        );
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%2"), "%zz%2");
+        assert_eq!(percent_decode("%C3%A9"), "\u{e9}");
+    }
+
+    #[test]
+    fn osc_1337() {
+        assert_eq!(
+            scan(&[b"\x1B]1337;RemoteHost=user@host\x07"]),
+            (Some(location(Some("host"), Some("user"), None)), false)
+        );
+        assert_eq!(
+            scan(&[b"\x1B]1337;RemoteHost=host\x07"]),
+            (Some(location(Some("host"), None, None)), false)
+        );
+        assert_eq!(
+            scan(&[b"\x1B]1337;CurrentDir=/tmp\x07"]),
+            (Some(location(None, None, Some("/tmp"))), true)
+        );
+    }
+
+    #[test]
+    fn osc_133_prompt() {
+        assert_eq!(scan(&[b"\x1B]133;A\x07"]), (None, true));
+        assert_eq!(scan(&[b"\x1B]133;C\x07"]), (None, false));
+    }
+
+    #[test]
+    fn integration_detected() {
+        let shared = SharedShellLocation::default();
+        let mut scanner = OscScanner::new(shared.clone());
+        assert!(!shared.output());
+        scanner.scan(b"$ \x1B]0;title\x07\x1B]2;other\x1B\\");
+        assert!(shared.output());
+        assert!(!shared.integrated());
+        scanner.scan(b"\x1B]133;B\x07");
+        assert!(shared.integrated());
+
+        shared.reset();
+        assert!(!shared.output());
+        assert!(!shared.integrated());
+    }
+
+    #[test]
+    fn osc_oversized() {
+        let mut osc = b"\x1B]7;file://host/".to_vec();
+        osc.resize(MAX_OSC_LEN * 2, b'a');
+        osc.push(0x07);
+        assert_eq!(scan(&[&osc]), (None, false));
+
+        // Later sequences are still scanned
+        assert_eq!(
+            scan(&[&osc, b"\x1B]7;file://host/tmp\x07"]),
+            (Some(location(Some("host"), None, Some("/tmp"))), true)
+        );
+    }
+}
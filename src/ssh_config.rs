@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::Profile, localize::LANGUAGE_SORTER};
+
+// Same limit as the ssh client
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Directory of the ssh client configuration, watched for changes
+pub fn ssh_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".ssh"))
+}
+
+/// Get hosts from `~/.ssh/config` and included files, skipping patterns with wildcards
+pub fn ssh_hosts() -> Vec<String> {
+    let mut hosts = Vec::new();
+    if let Some(ssh_dir) = ssh_dir() {
+        let mut paths = Vec::new();
+        read_config(&ssh_dir, &ssh_dir.join("config"), 0, &mut paths, &mut hosts);
+    }
+    hosts.sort_by(|a, b| LANGUAGE_SORTER.compare(a, b));
+    hosts.dedup();
+    hosts
+}
+
+/// ID of a host, used by menu actions as they can not hold the host name
+pub fn host_id(host: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    host.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Dynamic profile connecting to a host, can be pinned by adding it to the profiles
+pub fn ssh_profile(host: &str) -> Profile {
+    Profile {
+        name: format!("ssh {}", host),
+        command: format!(
+            "ssh {}",
+            shlex::try_quote(host).unwrap_or_else(|_| host.into())
+        ),
+        tab_title: host.to_string(),
+        ..Default::default()
+    }
+}
+
+fn read_config(
+    ssh_dir: &Path,
+    path: &Path,
+    depth: usize,
+    paths: &mut Vec<PathBuf>,
+    hosts: &mut Vec<String>,
+) {
+    if depth > MAX_INCLUDE_DEPTH || paths.iter().any(|x| x == path) {
+        return;
+    }
+    paths.push(path.to_path_buf());
+
+    let contents = match fs::read_to_string(path) {
+        Ok(ok) => ok,
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                log::warn!("failed to read ssh config {:?}: {}", path, err);
+            }
+            return;
+        }
+    };
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Keywords are separated from arguments by whitespace or an equals sign
+        let Some((keyword, args)) = line.split_once(|c: char| c.is_whitespace() || c == '=') else {
+            continue;
+        };
+        let args = args.trim_start_matches(|c: char| c.is_whitespace() || c == '=');
+        let args = shlex::split(args).unwrap_or_default();
+
+        if keyword.eq_ignore_ascii_case("Host") {
+            for host in args {
+                if host.starts_with('!') || host.contains(['*', '?']) {
+                    continue;
+                }
+                hosts.push(host);
+            }
+        } else if keyword.eq_ignore_ascii_case("Include") {
+            for pattern in args {
+                let pattern = match pattern.strip_prefix("~/") {
+                    Some(rest) => ssh_dir.parent().unwrap_or(ssh_dir).join(rest),
+                    // Relative paths are relative to ~/.ssh
+                    None => ssh_dir.join(pattern),
+                };
+                for include_path in glob(&pattern) {
+                    read_config(ssh_dir, &include_path, depth + 1, paths, hosts);
+                }
+            }
+        }
+    }
+}
+
+// Expand wildcards in the file name, sorted like the ssh client does
+fn glob(pattern: &Path) -> Vec<PathBuf> {
+    let Some(file_pattern) = pattern.file_name().and_then(|x| x.to_str()) else {
+        return Vec::new();
+    };
+    if !file_pattern.contains(['*', '?']) {
+        return vec![pattern.to_path_buf()];
+    }
+
+    let Some(dir) = pattern.parent() else {
+        return Vec::new();
+    };
+    let mut paths = Vec::new();
+    match fs::read_dir(dir) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                let file_name = entry.file_name();
+                if let Some(file_name) = file_name.to_str() {
+                    if wildcard_match(file_pattern.as_bytes(), file_name.as_bytes()) {
+                        paths.push(entry.path());
+                    }
+                }
+            }
+        }
+        Err(err) => {
+            if err.kind() != io::ErrorKind::NotFound {
+                log::warn!("failed to read ssh config directory {:?}: {}", dir, err);
+            }
+        }
+    }
+    paths.sort();
+    paths
+}
+
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        Some((b'*', rest)) => (0..=name.len()).any(|i| wildcard_match(rest, &name[i..])),
+        Some((b'?', rest)) => !name.is_empty() && wildcard_match(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && wildcard_match(rest, &name[1..]),
+        None => name.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Read hosts from a config in a temporary ~/.ssh with the given files
+    fn hosts(files: &[(&str, &str)]) -> Vec<String> {
+        let home = tempfile::tempdir().unwrap();
+        let ssh_dir = home.path().join(".ssh");
+        for (name, contents) in files {
+            let path = ssh_dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        let mut paths = Vec::new();
+        let mut hosts = Vec::new();
+        read_config(&ssh_dir, &ssh_dir.join("config"), 0, &mut paths, &mut hosts);
+        hosts
+    }
+
+    fn check(rows: &[(&str, &[&str])]) {
+        for (config, expected) in rows {
+            assert_eq!(hosts(&[("config", config)]), *expected, "{:?}", config);
+        }
+    }
+
+    #[test]
+    fn host_lists() {
+        check(&[
+            ("Host a", &["a"]),
+            ("Host a b\nHost c", &["a", "b", "c"]),
+            ("host a\n  HOST b", &["a", "b"]),
+            ("# Host a\n\nHost b", &["b"]),
+            // Negated patterns only exclude hosts from the other patterns
+            ("Host a !b c", &["a", "c"]),
+            ("Host !a", &[]),
+            ("HostName a\nUser b", &[]),
+        ]);
+    }
+
+    #[test]
+    fn host_wildcards() {
+        check(&[
+            ("Host *", &[]),
+            ("Host a *.example.com b", &["a", "b"]),
+            ("Host server? a", &["a"]),
+        ]);
+    }
+
+    #[test]
+    fn host_arguments() {
+        check(&[
+            ("Host=a", &["a"]),
+            ("Host = a b", &["a", "b"]),
+            ("Host \"a\" 'b'", &["a", "b"]),
+            ("Host=\"a b\" c", &["a b", "c"]),
+            ("Host \"unterminated", &[]),
+        ]);
+    }
+
+    #[test]
+    fn include_relative_to_ssh_dir() {
+        assert_eq!(
+            hosts(&[
+                ("config", "Include other conf.d/*.conf ~/.ssh/home\nHost a"),
+                ("other", "Host b"),
+                ("conf.d/2.conf", "Host d"),
+                ("conf.d/1.conf", "Host c"),
+                ("conf.d/ignored", "Host ignored"),
+                ("home", "Host e"),
+            ]),
+            ["b", "c", "d", "e", "a"]
+        );
+
+        // Missing files are ignored and files are only read once
+        assert_eq!(
+            hosts(&[
+                ("config", "Include missing config other\nHost a"),
+                ("other", "Host b")
+            ]),
+            ["b", "a"]
+        );
+    }
+
+    #[test]
+    fn include_depth() {
+        let names: Vec<String> = (1..=20).map(|i| i.to_string()).collect();
+        let mut files = vec![("config".to_string(), "Include 1".to_string())];
+        for (i, name) in names.iter().enumerate() {
+            files.push((name.clone(), format!("Host h{}\nInclude {}", name, i + 2)));
+        }
+        let files: Vec<(&str, &str)> = files
+            .iter()
+            .map(|(name, contents)| (name.as_str(), contents.as_str()))
+            .collect();
+
+        // Includes are followed as deep as the ssh client does
+        let expected: Vec<String> = (1..=MAX_INCLUDE_DEPTH).map(|i| format!("h{}", i)).collect();
+        assert_eq!(hosts(&files), expected);
+    }
+
+    #[test]
+    fn host_ids() {
+        assert_eq!(host_id("a"), host_id("a"));
+        assert_ne!(host_id("a"), host_id("b"));
+    }
+}
@@ -8,11 +8,11 @@ use alacritty_terminal::{
     term::{
         cell::Flags,
         color::{self, Colors},
-        search::RegexSearch,
+        search::{Match, RegexIter, RegexSearch},
         viewport_to_point, Config, TermDamage, TermMode,
     },
     tty::{self, Options},
-    vte::ansi::{Color, NamedColor, Rgb},
+    vte::ansi::{Color, Handler, NamedColor, Rgb},
     Term,
 };
 use cosmic::{
@@ -21,8 +21,8 @@ use cosmic::{
     widget::{pane_grid, segmented_button},
 };
 use cosmic_text::{
-    Attrs, AttrsList, Buffer, BufferLine, CacheKeyFlags, Family, LineEnding, Metrics, Shaping,
-    Weight, Wrap,
+    Attrs, AttrsList, AttrsOwned, Buffer, BufferLine, CacheKeyFlags, Family, FamilyOwned,
+    LineEnding, Metrics, Shaping, Weight, Wrap,
 };
 use indexmap::IndexSet;
 use std::{
@@ -40,10 +40,16 @@ use tokio::sync::mpsc;
 pub use alacritty_terminal::grid::Scroll as TerminalScroll;
 
 use crate::{
-    config::{ColorSchemeKind, Config as AppConfig, ProfileId},
+    config::{CloseOnExit, ColorSchemeKind, Config as AppConfig, ProfileId},
+    copy_mode::{CopyMode, CopyModeAction},
     mouse_reporter::MouseReporter,
+    shell_integration::SharedShellLocation,
+    terminal_theme::{self, HighlightColors, TerminalTheme},
 };
 
+#[cfg(unix)]
+use crate::shell_integration::ShellIntegrationPty;
+
 /// Minimum contrast between a fixed cursor color and the cell's background.
 /// Duplicated from alacritty
 pub const MIN_CURSOR_CONTRAST: f64 = 1.5;
@@ -109,8 +115,54 @@ fn as_dim(mut color: Color) -> Color {
     color
 }
 
+fn rgb_color(rgb: Rgb) -> cosmic_text::Color {
+    cosmic_text::Color::rgb(rgb.r, rgb.g, rgb.b)
+}
+
+fn color_rgb(color: cosmic_text::Color) -> Rgb {
+    Rgb {
+        r: color.r(),
+        g: color.g(),
+        b: color.b(),
+    }
+}
+
+// Pick whichever of two colors has the better contrast against a background
+fn contrasting(
+    bg: cosmic_text::Color,
+    a: cosmic_text::Color,
+    b: cosmic_text::Color,
+) -> cosmic_text::Color {
+    if color_rgb(a).contrast(color_rgb(bg)) >= color_rgb(b).contrast(color_rgb(bg)) {
+        a
+    } else {
+        b
+    }
+}
+
+// Resolve a pair of highlight colors, a missing foreground keeps the cell foreground unless
+// neither color is set
+fn highlight(
+    fg_opt: Option<Rgb>,
+    bg_opt: Option<Rgb>,
+    default: (Option<cosmic_text::Color>, cosmic_text::Color),
+) -> (Option<cosmic_text::Color>, cosmic_text::Color) {
+    match (fg_opt, bg_opt) {
+        (None, None) => default,
+        (fg_opt, Some(bg)) => (fg_opt.map(rgb_color), rgb_color(bg)),
+        (Some(fg), None) => (Some(rgb_color(fg)), default.1),
+    }
+}
+
 pub static WINDOW_BG_COLOR: AtomicU32 = AtomicU32::new(0xFF000000);
 
+fn font_family(font_name_opt: Option<&str>) -> Family<'_> {
+    match font_name_opt {
+        Some(font_name) => Family::Name(font_name),
+        None => Family::Monospace,
+    }
+}
+
 fn convert_color(colors: &Colors, color: Color) -> cosmic_text::Color {
     let rgb = match color {
         Color::Named(named_color) => match colors[named_color] {
@@ -135,7 +187,7 @@ fn convert_color(colors: &Colors, color: Color) -> cosmic_text::Color {
             }
         }
     };
-    cosmic_text::Color::rgb(rgb.r, rgb.g, rgb.b)
+    rgb_color(rgb)
 }
 
 type TabModel = segmented_button::Model<segmented_button::SingleSelect>;
@@ -194,23 +246,92 @@ impl Metadata {
     }
 }
 
+/// The focused search match. Its points move up as output pushes lines into history, so they are
+/// adjusted by the growth of the history and checked against the text that was matched.
+struct SearchMatch {
+    range: Match,
+    history_size: usize,
+    text: String,
+}
+
+impl SearchMatch {
+    fn new(range: Match, term: &Term<EventProxy>) -> Self {
+        Self {
+            history_size: term.grid().history_size(),
+            text: term.bounds_to_string(*range.start(), *range.end()),
+            range,
+        }
+    }
+
+    /// Get the current position of the match, if it is still in the grid
+    fn update(&mut self, term: &Term<EventProxy>) -> Option<&Match> {
+        let grid = term.grid();
+        let history_size = grid.history_size();
+        if history_size != self.history_size {
+            let delta = history_size as i32 - self.history_size as i32;
+            let (start, end) = (*self.range.start(), *self.range.end());
+            self.range = Point::new(Line(start.line.0 - delta), start.column)
+                ..=Point::new(Line(end.line.0 - delta), end.column);
+            self.history_size = history_size;
+        }
+
+        // Once history is full, or after it is cleared, only the text shows if the match moved
+        let (start, end) = (*self.range.start(), *self.range.end());
+        if start.line < grid.topmost_line()
+            || end.line > grid.bottommost_line()
+            || term.bounds_to_string(start, end) != self.text
+        {
+            return None;
+        }
+        Some(&self.range)
+    }
+}
+
 pub struct Terminal {
+    pub close_on_exit: CloseOnExit,
+    /// Color scheme of this tab, overriding the one of its profile
+    pub color_scheme_override: Option<(String, ColorSchemeKind)>,
+    /// Name of the container this terminal runs in, shown in the tab title
+    pub container_opt: Option<String>,
     pub context_menu: Option<cosmic::iced::Point>,
+    pub copy_mode: CopyMode,
+    /// Set when the process exited and the tab was kept open
+    pub exited: bool,
+    /// Exit status of the process, if it exited normally
+    pub exit_status: Option<i32>,
     pub metadata_set: IndexSet<Metadata>,
     pub needs_update: bool,
     pub profile_id_opt: Option<ProfileId>,
     pub tab_title_override: Option<String>,
     pub term: Arc<FairMutex<Term<EventProxy>>>,
+    // Profile and tab title the terminal was opened with, restored when no rule matches
+    base_profile_id_opt: Option<ProfileId>,
+    base_tab_title_override: Option<String>,
     bold_font_weight: Weight,
     buffer: Arc<Buffer>,
+    // Color scheme colors with kept and current program colors applied
     colors: Colors,
-    default_attrs: Attrs<'static>,
+    default_attrs: AttrsOwned,
     dim_font_weight: Weight,
+    event_proxy: EventProxy,
+    highlight_colors: HighlightColors,
+    // Input typed into the shell when it shows its first prompt
+    initial_input: String,
+    initial_input_pending: bool,
+    // Colors set by programs that are kept when the program resets them
+    kept_colors: Colors,
+    minimum_contrast: Option<f64>,
     mouse_reporter: MouseReporter,
     notifier: Notifier,
+    // Options the process was started with, used when restarting it
+    options: Options,
+    scheme_colors: Colors,
+    search_match_opt: Option<SearchMatch>,
     search_regex_opt: Option<RegexSearch>,
     search_value: String,
+    shell_location: SharedShellLocation,
     size: Size,
+    term_config: Config,
     use_bright_bold: bool,
 }
 
@@ -223,7 +344,7 @@ impl Terminal {
         config: Config,
         options: Options,
         app_config: &AppConfig,
-        colors: Colors,
+        theme: TerminalTheme,
         profile_id_opt: Option<ProfileId>,
         tab_title_override: Option<String>,
     ) -> Result<Self, io::Error> {
@@ -232,9 +353,15 @@ impl Terminal {
         let dim_font_weight = app_config.dim_font_weight;
         let bold_font_weight = app_config.bold_font_weight;
         let use_bright_bold = app_config.use_bright_bold;
+        let minimum_contrast = app_config.minimum_contrast();
 
+        let font_family = font_family(app_config.font_name(profile_id_opt));
         let metrics = Metrics::new(14.0, 20.0);
 
+        let TerminalTheme {
+            colors,
+            highlight_colors,
+        } = theme;
         let default_bg = convert_color(&colors, Color::Named(NamedColor::Background));
         let default_fg = convert_color(&colors, Color::Named(NamedColor::Foreground));
 
@@ -244,7 +371,7 @@ impl Terminal {
 
         //TODO: set color to default fg
         let default_attrs = Attrs::new()
-            .family(Family::Monospace)
+            .family(font_family)
             .weight(Weight(font_weight))
             .stretch(font_stretch)
             .color(default_fg)
@@ -272,36 +399,65 @@ impl Terminal {
             cell_height,
         };
         let event_proxy = EventProxy(pane, entity, event_tx);
+        let mut config = config;
+        config.scrolling_history = app_config.scrolling_history(profile_id_opt) as usize;
         let term = Arc::new(FairMutex::new(Term::new(
-            config,
+            config.clone(),
             &size,
             event_proxy.clone(),
         )));
 
         let window_id = 0;
         let pty = tty::new(&options, size.into(), window_id)?;
+        let shell_location = SharedShellLocation::default();
+        #[cfg(unix)]
+        let pty = ShellIntegrationPty::new(pty, shell_location.clone())?;
 
-        let pty_event_loop = EventLoop::new(term.clone(), event_proxy, pty, options.hold, false)?;
+        let pty_event_loop =
+            EventLoop::new(term.clone(), event_proxy.clone(), pty, options.hold, false)?;
         let notifier = Notifier(pty_event_loop.channel());
         let _pty_join_handle = pty_event_loop.spawn();
 
         Ok(Self {
+            base_profile_id_opt: profile_id_opt,
+            base_tab_title_override: tab_title_override.clone(),
             bold_font_weight: Weight(bold_font_weight),
             buffer: Arc::new(buffer),
+            close_on_exit: if options.hold {
+                CloseOnExit::Never
+            } else {
+                CloseOnExit::Always
+            },
+            color_scheme_override: None,
+            container_opt: None,
             colors,
             context_menu: None,
-            default_attrs,
+            copy_mode: CopyMode::default(),
+            default_attrs: AttrsOwned::new(default_attrs),
             dim_font_weight: Weight(dim_font_weight),
+            event_proxy,
+            exited: false,
+            exit_status: None,
+            highlight_colors,
+            initial_input: String::new(),
+            initial_input_pending: false,
+            kept_colors: Colors::default(),
             metadata_set,
+            minimum_contrast,
             mouse_reporter: Default::default(),
             needs_update: true,
             notifier,
+            options,
             profile_id_opt,
+            scheme_colors: colors,
+            search_match_opt: None,
             search_regex_opt: None,
             search_value: String::new(),
+            shell_location,
             size,
             tab_title_override,
             term,
+            term_config: config,
             use_bright_bold,
         })
     }
@@ -324,8 +480,8 @@ impl Terminal {
         &self.colors
     }
 
-    pub fn default_attrs(&self) -> &Attrs<'static> {
-        &self.default_attrs
+    pub fn default_attrs(&self) -> Attrs {
+        self.default_attrs.as_attrs()
     }
 
     pub fn size(&self) -> Size {
@@ -433,6 +589,7 @@ impl Terminal {
                     Ok(search_regex) => {
                         self.search_regex_opt = Some(search_regex);
                         self.search_value = value.to_string();
+                        self.search_match_opt = None;
                         term.selection = None;
                     }
                     Err(err) => {
@@ -447,28 +604,39 @@ impl Terminal {
             };
 
             // Determine search origin
+            let copy_mode = term.mode().contains(TermMode::VI);
             let grid = term.grid();
-            let search_origin = match term
-                .selection
-                .as_ref()
-                .and_then(|selection| selection.to_range(&term))
-            {
-                Some(range) => {
-                    //TODO: determine correct search_origin, along with side below
-                    if forwards {
-                        range.end.add(grid, Boundary::Grid, 1)
-                    } else {
-                        range.start.sub(grid, Boundary::Grid, 1)
-                    }
+            let search_origin = if copy_mode {
+                // In copy mode, search starts next to the copy mode cursor
+                let point = term.vi_mode_cursor.point;
+                if forwards {
+                    point.add(grid, Boundary::Grid, 1)
+                } else {
+                    point.sub(grid, Boundary::Grid, 1)
                 }
-                None => {
-                    if forwards {
-                        Point::new(Line(-(grid.history_size() as i32)), Column(0))
-                    } else {
-                        Point::new(
-                            Line(grid.screen_lines() as i32 - 1),
-                            Column(grid.columns() - 1),
-                        )
+            } else {
+                match term
+                    .selection
+                    .as_ref()
+                    .and_then(|selection| selection.to_range(&term))
+                {
+                    Some(range) => {
+                        //TODO: determine correct search_origin, along with side below
+                        if forwards {
+                            range.end.add(grid, Boundary::Grid, 1)
+                        } else {
+                            range.start.sub(grid, Boundary::Grid, 1)
+                        }
+                    }
+                    None => {
+                        if forwards {
+                            Point::new(Line(-(grid.history_size() as i32)), Column(0))
+                        } else {
+                            Point::new(
+                                Line(grid.screen_lines() as i32 - 1),
+                                Column(grid.columns() - 1),
+                            )
+                        }
                     }
                 }
             };
@@ -486,24 +654,37 @@ impl Terminal {
                 if forwards { Side::Left } else { Side::Right },
                 None,
             ) {
-                // Scroll to match
-                if forwards {
-                    term.scroll_to_point(*search_match.end());
+                if copy_mode {
+                    // Move copy mode cursor to match, extending any selection
+                    term.vi_goto_point(*search_match.start());
                 } else {
-                    term.scroll_to_point(*search_match.start());
-                }
+                    // Scroll to match
+                    if forwards {
+                        term.scroll_to_point(*search_match.end());
+                    } else {
+                        term.scroll_to_point(*search_match.start());
+                    }
 
-                // Set selection to match
-                let mut selection =
-                    Selection::new(SelectionType::Simple, *search_match.start(), Side::Left);
-                selection.update(*search_match.end(), Side::Right);
-                term.selection = Some(selection);
+                    // Set selection to match
+                    let mut selection =
+                        Selection::new(SelectionType::Simple, *search_match.start(), Side::Left);
+                    selection.update(*search_match.end(), Side::Right);
+                    term.selection = Some(selection);
+                }
+                self.search_match_opt = Some(SearchMatch::new(search_match, &term));
             }
         }
 
         self.update();
     }
 
+    /// Stop highlighting search matches, the search can still be repeated
+    pub fn search_end(&mut self) {
+        if self.search_match_opt.take().is_some() {
+            self.update();
+        }
+    }
+
     pub fn select_all(&mut self) {
         {
             let mut term = self.term.lock();
@@ -524,22 +705,226 @@ impl Terminal {
         self.update();
     }
 
+    pub fn is_copy_mode(&self) -> bool {
+        self.term.lock().mode().contains(TermMode::VI)
+    }
+
+    pub fn toggle_copy_mode(&mut self) {
+        {
+            let mut term = self.term.lock();
+            term.toggle_vi_mode();
+            term.selection = None;
+        }
+        self.copy_mode = CopyMode::default();
+        self.update();
+    }
+
+    pub fn copy_mode_action(&mut self, action: CopyModeAction) {
+        {
+            let mut term = self.term.lock();
+            match action {
+                CopyModeAction::Bottom => {
+                    let grid = term.grid();
+                    let point = Point::new(grid.bottommost_line(), Column(0));
+                    term.vi_goto_point(point);
+                }
+                CopyModeAction::HalfPageDown | CopyModeAction::HalfPageUp => {
+                    let lines = (term.screen_lines() / 2) as i32;
+                    let lines = match action {
+                        CopyModeAction::HalfPageUp => lines,
+                        _ => -lines,
+                    };
+                    let vi_mode_cursor = term.vi_mode_cursor.scroll(&*term, lines);
+                    term.vi_goto_point(vi_mode_cursor.point);
+                }
+                CopyModeAction::Motion(motion) => {
+                    term.vi_motion(motion);
+                }
+                CopyModeAction::Select(selection_type) => {
+                    let toggle_off = term
+                        .selection
+                        .as_ref()
+                        .is_some_and(|selection| selection.ty == selection_type);
+                    if toggle_off {
+                        term.selection = None;
+                    } else {
+                        let point = term.vi_mode_cursor.point;
+                        let mut selection = Selection::new(selection_type, point, Side::Left);
+                        selection.include_all();
+                        term.selection = Some(selection);
+                    }
+                }
+                CopyModeAction::Top => {
+                    let grid = term.grid();
+                    let point = Point::new(grid.topmost_line(), Column(0));
+                    term.vi_goto_point(point);
+                }
+                CopyModeAction::Exit
+                | CopyModeAction::Search(_)
+                | CopyModeAction::SearchNext
+                | CopyModeAction::SearchPrevious
+                | CopyModeAction::Yank => {
+                    log::warn!("copy mode action {:?} must be handled by caller", action);
+                }
+            }
+        }
+        self.update();
+    }
+
+    /// Repeat the last search in the given direction
+    pub fn search_repeat(&mut self, forwards: bool) {
+        if !self.search_value.is_empty() {
+            let value = self.search_value.clone();
+            self.search(&value, forwards);
+        }
+    }
+
+    /// Record that the process exited, returns true if the tab should be closed
+    pub fn process_exited(&mut self, exit_status_opt: Option<i32>) -> bool {
+        self.exited = true;
+        if exit_status_opt.is_some() {
+            self.exit_status = exit_status_opt;
+        }
+        self.needs_update = true;
+        match self.close_on_exit {
+            CloseOnExit::Always => true,
+            CloseOnExit::Never => false,
+            CloseOnExit::Clean => self.exit_status == Some(0),
+        }
+    }
+
+    /// Check if the process exited without an exit event since the last call
+    pub fn take_exited(&self) -> bool {
+        self.shell_location.take_exited() && !self.exited
+    }
+
+    /// Start the process again after it exited, with the same options
+    pub fn restart(&mut self) -> Result<(), io::Error> {
+        let window_id = 0;
+        let pty = tty::new(&self.options, self.size.into(), window_id)?;
+        #[cfg(unix)]
+        let pty = ShellIntegrationPty::new(pty, self.shell_location.clone())?;
+
+        let pty_event_loop = EventLoop::new(
+            self.term.clone(),
+            self.event_proxy.clone(),
+            pty,
+            self.options.hold,
+            false,
+        )?;
+        // The previous event loop stops when its process exits, this is only a precaution
+        let _ = self.notifier.0.send(Msg::Shutdown);
+        self.notifier = Notifier(pty_event_loop.channel());
+
+        // Reset before the new process can write output
+        self.term.lock().reset_state();
+        self.exited = false;
+        self.exit_status = None;
+        self.needs_update = true;
+
+        // Type the initial input again when the new shell is ready
+        self.shell_location.reset();
+        self.initial_input_pending = !self.initial_input.is_empty();
+
+        let _pty_join_handle = pty_event_loop.spawn();
+        Ok(())
+    }
+
+    /// Set input to type into the shell once it shows its first prompt
+    pub fn set_initial_input(&mut self, initial_input: String) {
+        self.initial_input_pending = !initial_input.is_empty();
+        self.initial_input = initial_input;
+    }
+
+    pub fn initial_input_pending(&self) -> bool {
+        self.initial_input_pending
+    }
+
+    /// Type the initial input when the shell showed a prompt
+    pub fn update_initial_input(&mut self) {
+        if self.initial_input_pending && self.shell_location.take_prompt() {
+            self.type_initial_input();
+        }
+    }
+
+    /// Type the initial input into shells without shell integration, which never report their
+    /// prompt. Returns true if the shell printed nothing yet, so it should be checked again.
+    pub fn initial_input_timeout(&mut self) -> bool {
+        if !self.initial_input_pending || self.exited || self.shell_location.integrated() {
+            return false;
+        }
+        if !self.shell_location.output() {
+            return true;
+        }
+        self.type_initial_input();
+        false
+    }
+
+    fn type_initial_input(&mut self) {
+        self.initial_input_pending = false;
+        let mut input = self.initial_input.trim_end().to_string();
+        input.push('\r');
+        self.input_no_scroll(input.into_bytes());
+    }
+
+    /// Switch to the profile of the first rule matching the shell location, or back to the base
+    /// profile. Returns true if the profile changed.
+    pub fn update_profile_from_rules(&mut self, config: &AppConfig) -> bool {
+        let Some(location) = self.shell_location.take_changed() else {
+            return false;
+        };
+        let profile_id_opt = config
+            .matching_profile(&location)
+            .or(self.base_profile_id_opt);
+        if profile_id_opt == self.profile_id_opt {
+            return false;
+        }
+
+        self.profile_id_opt = profile_id_opt;
+        self.tab_title_override = if profile_id_opt == self.base_profile_id_opt {
+            self.base_tab_title_override.clone()
+        } else {
+            profile_id_opt
+                .and_then(|profile_id| config.profiles.get(&profile_id))
+                .filter(|profile| !profile.tab_title.is_empty())
+                .map(|profile| profile.tab_title.clone())
+                .or_else(|| self.base_tab_title_override.clone())
+        };
+        true
+    }
+
+    /// Stop using a removed profile, falling back to the default profile
+    pub fn remove_profile(&mut self, profile_id: ProfileId) {
+        if self.base_profile_id_opt == Some(profile_id) {
+            self.base_profile_id_opt = None;
+        }
+        if self.profile_id_opt == Some(profile_id) {
+            self.profile_id_opt = self.base_profile_id_opt;
+        }
+    }
+
     pub fn set_config(
         &mut self,
         config: &AppConfig,
-        themes: &HashMap<(String, ColorSchemeKind), Colors>,
+        themes: &HashMap<(String, ColorSchemeKind), TerminalTheme>,
         zoom_adj: i8,
     ) {
         let mut update_cell_size = false;
         let mut update = false;
 
+        let font_family = FamilyOwned::new(font_family(config.font_name(self.profile_id_opt)));
+        if self.default_attrs.family_owned != font_family {
+            self.default_attrs.family_owned = font_family;
+            update_cell_size = true;
+        }
+
         if self.default_attrs.stretch != config.typed_font_stretch() {
-            self.default_attrs = self.default_attrs.stretch(config.typed_font_stretch());
+            self.default_attrs.stretch = config.typed_font_stretch();
             update_cell_size = true;
         }
 
         if self.default_attrs.weight.0 != config.font_weight {
-            self.default_attrs = self.default_attrs.weight(Weight(config.font_weight));
+            self.default_attrs.weight = Weight(config.font_weight);
             update_cell_size = true;
         }
 
@@ -558,7 +943,20 @@ impl Terminal {
             update_cell_size = true;
         }
 
-        let metrics = config.metrics(zoom_adj);
+        if self.minimum_contrast != config.minimum_contrast() {
+            self.minimum_contrast = config.minimum_contrast();
+            update = true;
+        }
+
+        // Shrinking the scrollback drops the oldest lines
+        let scrolling_history = config.scrolling_history(self.profile_id_opt) as usize;
+        if self.term_config.scrolling_history != scrolling_history {
+            self.term_config.scrolling_history = scrolling_history;
+            self.term.lock().set_options(self.term_config.clone());
+            update = true;
+        }
+
+        let metrics = config.metrics(self.profile_id_opt, zoom_adj);
         if metrics != self.buffer.metrics() {
             {
                 let mut font_system = font_system().write().unwrap();
@@ -567,14 +965,32 @@ impl Terminal {
             update_cell_size = true;
         }
 
-        if let Some(colors) = themes.get(&config.syntax_theme(self.profile_id_opt)) {
+        let (theme_name, color_scheme_kind) = match &self.color_scheme_override {
+            Some(color_scheme_override) => color_scheme_override.clone(),
+            None => config.syntax_theme(self.profile_id_opt),
+        };
+        if let Some(theme) = themes.get(&(theme_name, color_scheme_kind)) {
+            let mut colors = theme.colors;
+            // The window background only suits color schemes matching the application theme
+            if colors[NamedColor::Background].is_none()
+                && color_scheme_kind != config.color_scheme_kind()
+            {
+                colors[NamedColor::Background] =
+                    Some(terminal_theme::window_background(color_scheme_kind));
+            }
+
+            // Program colors are applied over these in update
             let mut changed = false;
             for i in 0..color::COUNT {
-                if self.colors[i] != colors[i] {
-                    self.colors[i] = colors[i];
+                if self.scheme_colors[i] != colors[i] {
+                    self.scheme_colors[i] = colors[i];
                     changed = true;
                 }
             }
+            if self.highlight_colors != theme.highlight_colors {
+                self.highlight_colors = theme.highlight_colors;
+                changed = true;
+            }
             if changed {
                 update = true;
             }
@@ -592,6 +1008,16 @@ impl Terminal {
     }
 
     pub fn update_colors(&mut self, config: &AppConfig) {
+        self.default_attrs = AttrsOwned::new(
+            Attrs::new()
+                .family(font_family(config.font_name(self.profile_id_opt)))
+                .weight(Weight(config.font_weight))
+                .stretch(config.typed_font_stretch()),
+        );
+        self.update_default_colors();
+    }
+
+    fn update_default_colors(&mut self) {
         self.metadata_set.clear();
         let default_bg = convert_color(&self.colors, Color::Named(NamedColor::Background));
         let default_fg = convert_color(&self.colors, Color::Named(NamedColor::Foreground));
@@ -599,23 +1025,49 @@ impl Terminal {
         let default_metadata = Metadata::new(default_bg, default_fg);
         let (default_metadata_idx, _) = self.metadata_set.insert_full(default_metadata);
 
-        self.default_attrs = Attrs::new()
-            .family(Family::Monospace)
-            .weight(Weight(config.font_weight))
-            .stretch(config.typed_font_stretch())
-            .color(default_fg)
-            .metadata(default_metadata_idx);
+        self.default_attrs.color_opt = Some(default_fg);
+        self.default_attrs.metadata = default_metadata_idx;
+    }
+
+    /// Keep the colors currently set by programs, even after programs reset them
+    pub fn keep_program_colors(&mut self) {
+        {
+            let term = self.term.lock();
+            for i in 0..color::COUNT {
+                if let Some(rgb) = term.colors()[i] {
+                    self.kept_colors[i] = Some(rgb);
+                }
+            }
+        }
+        self.update();
+    }
+
+    /// Reset colors set by programs, including kept ones, to the color scheme colors
+    pub fn reset_program_colors(&mut self) {
+        self.kept_colors = Colors::default();
+        {
+            let mut term = self.term.lock();
+            for i in 0..color::COUNT {
+                term.reset_color(i);
+            }
+        }
+        self.update();
     }
 
     pub fn update_cell_size(&mut self) {
-        let default_attrs = self.default_attrs;
+        let default_attrs = self.default_attrs.clone();
         let (cell_width, cell_height) = {
             let mut font_system = font_system().write().unwrap();
             self.with_buffer_mut(|buffer| {
                 buffer.set_wrap(font_system.raw(), Wrap::None);
 
                 // Use size of space to determine cell size
-                buffer.set_text(font_system.raw(), " ", default_attrs, Shaping::Advanced);
+                buffer.set_text(
+                    font_system.raw(),
+                    " ",
+                    default_attrs.as_attrs(),
+                    Shaping::Advanced,
+                );
                 let layout = buffer.line_layout(font_system.raw(), 0).unwrap();
                 let w = layout[0].w;
                 buffer.set_monospace_width(font_system.raw(), Some(w));
@@ -644,6 +1096,22 @@ impl Terminal {
 
         let instant = Instant::now();
 
+        // Apply colors set by programs, current ones taking priority over kept ones
+        let colors = {
+            let term = self.term.lock();
+            let mut colors = self.scheme_colors;
+            for i in 0..color::COUNT {
+                if let Some(rgb) = term.colors()[i].or(self.kept_colors[i]) {
+                    colors[i] = Some(rgb);
+                }
+            }
+            colors
+        };
+        if (0..color::COUNT).any(|i| self.colors[i] != colors[i]) {
+            self.colors = colors;
+            self.update_default_colors();
+        }
+
         // Only keep default
         self.metadata_set.truncate(1);
 
@@ -655,7 +1123,7 @@ impl Terminal {
             let mut line_i = 0;
             let mut last_point = None;
             let mut text = String::from(LRI);
-            let mut attrs_list = AttrsList::new(self.default_attrs);
+            let mut attrs_list = AttrsList::new(self.default_attrs.as_attrs());
             {
                 let mut term = self.term.lock();
                 //TODO: use damage?
@@ -666,13 +1134,71 @@ impl Terminal {
                 term.reset_damage();
 
                 let grid = term.grid();
+
+                // Search matches in view are only highlighted while searching
+                let focused_match_opt = self
+                    .search_match_opt
+                    .as_mut()
+                    .and_then(|search_match| search_match.update(&term).cloned());
+                let mut search_matches = Vec::new();
+                if self.search_match_opt.is_some() {
+                    if let Some(search_regex) = &mut self.search_regex_opt {
+                        let display_offset = grid.display_offset() as i32;
+                        let start = Point::new(Line(-display_offset), Column(0));
+                        let end = Point::new(
+                            Line(grid.screen_lines() as i32 - 1 - display_offset),
+                            grid.last_column(),
+                        );
+                        search_matches.extend(RegexIter::new(
+                            start,
+                            end,
+                            Direction::Right,
+                            &term,
+                            search_regex,
+                        ));
+                    }
+                }
+                let mut search_matches = search_matches.into_iter().peekable();
+                let selection_range = term
+                    .selection
+                    .as_ref()
+                    .and_then(|selection| selection.to_range(&term));
+
+                // Resolve highlight colors, deriving unset ones from the terminal colors
+                let default_fg = convert_color(&self.colors, Color::Named(NamedColor::Foreground));
+                let default_bg = convert_color(&self.colors, Color::Named(NamedColor::Background));
+                let highlight_colors = &self.highlight_colors;
+                let selection_colors = highlight(
+                    highlight_colors.selection_foreground,
+                    highlight_colors.selection_background,
+                    (Some(default_bg), default_fg),
+                );
+                let search_match_bg = self.colors[NamedColor::Yellow].map_or(default_fg, rgb_color);
+                let search_match_colors = highlight(
+                    highlight_colors.search_match_foreground,
+                    highlight_colors.search_match_background,
+                    (
+                        Some(contrasting(search_match_bg, default_fg, default_bg)),
+                        search_match_bg,
+                    ),
+                );
+                // The focused match used to be drawn as the selection
+                let focused_match_colors = highlight(
+                    highlight_colors.focused_match_foreground,
+                    highlight_colors.focused_match_background,
+                    selection_colors,
+                );
+
+                // Foreground colors adjusted for minimum contrast, by foreground and background
+                let mut contrast_cache = HashMap::new();
+
                 for indexed in grid.display_iter() {
                     if indexed.point.line != last_point.unwrap_or(indexed.point).line {
                         while line_i >= buffer.lines.len() {
                             buffer.lines.push(BufferLine::new(
                                 "",
                                 LineEnding::default(),
-                                AttrsList::new(self.default_attrs),
+                                AttrsList::new(self.default_attrs.as_attrs()),
                                 Shaping::Advanced,
                             ));
                             buffer.set_redraw(true);
@@ -712,7 +1238,7 @@ impl Terminal {
                     }
                     let end = text.len();
 
-                    let mut attrs = self.default_attrs;
+                    let mut attrs = self.default_attrs.as_attrs();
 
                     let cell_fg = if indexed.cell.flags.contains(Flags::DIM) {
                         as_dim(indexed.cell.fg)
@@ -734,10 +1260,52 @@ impl Terminal {
                         )
                     };
 
-                    if indexed.cell.flags.contains(Flags::HIDDEN) {
+                    if indexed.cell.hyperlink().is_some() {
+                        if let Some(hyperlink) = self.highlight_colors.hyperlink {
+                            fg = rgb_color(hyperlink);
+                        }
+                    }
+
+                    let hidden = indexed.cell.flags.contains(Flags::HIDDEN);
+                    if hidden {
                         fg = bg;
                     }
 
+                    // Matches and cells are both in grid order, so passed matches are skipped
+                    while search_matches
+                        .next_if(|search_match| *search_match.end() < indexed.point)
+                        .is_some()
+                    {}
+                    let in_search_match = search_matches
+                        .peek()
+                        .is_some_and(|search_match| search_match.contains(&indexed.point));
+
+                    // Change color if highlighted, the focused match takes priority over the
+                    // selection, which takes priority over other matches
+                    let highlight_opt = if focused_match_opt
+                        .as_ref()
+                        .is_some_and(|search_match| search_match.contains(&indexed.point))
+                    {
+                        Some(focused_match_colors)
+                    } else if selection_range
+                        .as_ref()
+                        .is_some_and(|range| range.contains(indexed.point))
+                    {
+                        Some(selection_colors)
+                    } else if in_search_match {
+                        Some(search_match_colors)
+                    } else {
+                        None
+                    };
+                    if let Some((highlight_fg_opt, highlight_bg)) = highlight_opt {
+                        bg = highlight_bg;
+                        fg = if hidden {
+                            bg
+                        } else {
+                            highlight_fg_opt.unwrap_or(fg)
+                        };
+                    }
+
                     // Change color if cursor
                     if indexed.point == grid.cursor.point {
                         //TODO: better handling of cursor
@@ -779,14 +1347,23 @@ impl Terminal {
                         }
                     }
 
-                    // Change color if selected
-                    if let Some(selection) = &term.selection {
-                        if let Some(range) = selection.to_range(&term) {
-                            if range.contains(indexed.point) {
-                                //TODO: better handling of selection
-                                mem::swap(&mut fg, &mut bg);
-                            }
-                        }
+                    // Change color if copy mode cursor
+                    if term.mode().contains(TermMode::VI)
+                        && indexed.point == term.vi_mode_cursor.point
+                    {
+                        mem::swap(&mut fg, &mut bg);
+                    }
+
+                    // Enforce minimum contrast, hidden cells keep the background color as the
+                    // foreground color
+                    if let Some(minimum_contrast) = self.minimum_contrast {
+                        fg = *contrast_cache.entry((fg, bg)).or_insert_with(|| {
+                            rgb_color(terminal_theme::ensure_contrast(
+                                color_rgb(fg),
+                                color_rgb(bg),
+                                minimum_contrast,
+                            ))
+                        });
                     }
 
                     // Convert foreground to linear
@@ -827,7 +1404,7 @@ impl Terminal {
                 buffer.lines.push(BufferLine::new(
                     "",
                     LineEnding::default(),
-                    AttrsList::new(self.default_attrs),
+                    AttrsList::new(self.default_attrs.as_attrs()),
                     Shaping::Advanced,
                 ));
                 buffer.set_redraw(true);
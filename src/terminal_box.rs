@@ -17,6 +17,7 @@ use cosmic::{
     },
     iced_core::{
         clipboard::Clipboard,
+        image::{self, FilterMethod, Renderer as _},
         keyboard::key::Named,
         layout::{self, Layout},
         renderer::{self, Quad, Renderer as _},
@@ -41,7 +42,13 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crate::{key_bind::key_binds, terminal::Metadata, Action, Terminal, TerminalScroll};
+use crate::{
+    config::BackgroundImageMode, key_bind::key_binds, key_encoder, terminal::Metadata, Action,
+    Terminal, TerminalScroll,
+};
+
+/// Brightness of unfocused panes
+const UNFOCUSED_SHADE: f32 = 0.92;
 
 pub struct TerminalBox<'a, Message> {
     terminal: &'a Mutex<Terminal>,
@@ -53,6 +60,7 @@ pub struct TerminalBox<'a, Message> {
     on_context_menu: Option<Box<dyn Fn(Option<Point>) -> Message + 'a>>,
     on_mouse_enter: Option<Box<dyn Fn() -> Message + 'a>>,
     opacity: Option<f32>,
+    background_image: Option<(image::Handle, BackgroundImageMode, f32)>,
     mouse_inside_boundary: Option<bool>,
     on_middle_click: Option<Box<dyn Fn() -> Message + 'a>>,
     key_binds: HashMap<KeyBind, Action>,
@@ -73,6 +81,7 @@ where
             on_context_menu: None,
             on_mouse_enter: None,
             opacity: None,
+            background_image: None,
             mouse_inside_boundary: None,
             on_middle_click: None,
             key_binds: key_binds(),
@@ -126,6 +135,17 @@ where
         self.opacity = Some(opacity);
         self
     }
+
+    /// Draw an opaque image behind the terminal, dimmed by overlaying the background color.
+    pub fn background_image(
+        mut self,
+        handle: image::Handle,
+        mode: BackgroundImageMode,
+        dim: f32,
+    ) -> Self {
+        self.background_image = Some((handle, mode, dim));
+        self
+    }
 }
 
 pub fn terminal_box<Message>(terminal: &Mutex<Terminal>) -> TerminalBox<'_, Message>
@@ -272,24 +292,59 @@ where
         // Render default background
         {
             let meta = &terminal.metadata_set[terminal.default_attrs().metadata];
-            let background_color = shade(meta.bg, state.is_focused);
-
-            renderer.fill_quad(
-                Quad {
-                    bounds: layout.bounds(),
-                    border: self.border,
-                    ..Default::default()
-                },
-                Color::new(
-                    f32::from(background_color.r()) / 255.0,
-                    f32::from(background_color.g()) / 255.0,
-                    f32::from(background_color.b()) / 255.0,
-                    match self.opacity {
-                        Some(opacity) => opacity,
-                        None => f32::from(background_color.a()) / 255.0,
-                    },
-                ),
-            );
+            let quad = Quad {
+                bounds: layout.bounds(),
+                border: self.border,
+                ..Default::default()
+            };
+            match &self.background_image {
+                Some((handle, mode, dim)) => {
+                    //TODO: blur the image once the renderer supports it. It has no opacity
+                    // either, so opacity only applies to backgrounds without an image.
+                    let bounds = layout.bounds();
+                    renderer.with_layer(bounds, |renderer| {
+                        draw_background_image(renderer, handle.clone(), *mode, bounds);
+                    });
+
+                    // Shade the dimmed image as a whole, like other backgrounds
+                    renderer.fill_quad(
+                        quad,
+                        Color::new(
+                            f32::from(meta.bg.r()) / 255.0,
+                            f32::from(meta.bg.g()) / 255.0,
+                            f32::from(meta.bg.b()) / 255.0,
+                            *dim,
+                        ),
+                    );
+                    if !state.is_focused {
+                        renderer.fill_quad(
+                            Quad {
+                                border: Border {
+                                    radius: self.border.radius,
+                                    ..Default::default()
+                                },
+                                ..quad
+                            },
+                            Color::from_rgba(0.0, 0.0, 0.0, 1.0 - UNFOCUSED_SHADE),
+                        );
+                    }
+                }
+                None => {
+                    let background_color = shade(meta.bg, state.is_focused);
+                    renderer.fill_quad(
+                        quad,
+                        Color::new(
+                            f32::from(background_color.r()) / 255.0,
+                            f32::from(background_color.g()) / 255.0,
+                            f32::from(background_color.b()) / 255.0,
+                            match self.opacity {
+                                Some(opacity) => opacity,
+                                None => f32::from(background_color.a()) / 255.0,
+                            },
+                        ),
+                    );
+                }
+            }
         }
 
         // Render cell backgrounds that do not match default
@@ -598,230 +653,65 @@ where
         let mut terminal = self.terminal.lock().unwrap();
         let buffer_size = terminal.with_buffer(|buffer| buffer.size());
 
-        let is_app_cursor = terminal.term.lock().mode().contains(TermMode::APP_CURSOR);
         let is_mouse_mode = terminal.term.lock().mode().intersects(TermMode::MOUSE_MODE);
 
         let mut status = Status::Ignored;
         match event {
             Event::Keyboard(KeyEvent::KeyPressed {
-                key: Key::Named(named),
+                key,
+                location,
                 modifiers,
-                ..
+                text,
             }) if state.is_focused => {
                 for key_bind in self.key_binds.keys() {
-                    if key_bind.matches(modifiers, &Key::Named(named)) {
+                    if key_bind.matches(modifiers, &key) {
                         return Status::Captured;
                     }
                 }
-                let mod_no = calculate_modifier_number(state);
-                let escape_code = match named {
-                    Named::Insert => csi("2", "~", mod_no),
-                    Named::Delete => csi("3", "~", mod_no),
-                    Named::PageUp => {
-                        if modifiers.shift() {
-                            terminal.scroll(TerminalScroll::PageUp);
-                            None
-                        } else {
-                            csi("5", "~", mod_no)
-                        }
-                    }
-                    Named::PageDown => {
-                        if modifiers.shift() {
-                            terminal.scroll(TerminalScroll::PageDown);
-                            None
-                        } else {
-                            csi("6", "~", mod_no)
-                        }
-                    }
-                    Named::ArrowUp => {
-                        if is_app_cursor {
-                            ss3("A", mod_no)
-                        } else {
-                            csi("A", "", mod_no)
-                        }
-                    }
-                    Named::ArrowDown => {
-                        if is_app_cursor {
-                            ss3("B", mod_no)
-                        } else {
-                            csi("B", "", mod_no)
-                        }
-                    }
-                    Named::ArrowRight => {
-                        if is_app_cursor {
-                            ss3("C", mod_no)
-                        } else {
-                            csi("C", "", mod_no)
-                        }
-                    }
-                    Named::ArrowLeft => {
-                        if is_app_cursor {
-                            ss3("D", mod_no)
-                        } else {
-                            csi("D", "", mod_no)
-                        }
-                    }
-                    Named::End => {
-                        if modifiers.shift() {
-                            terminal.scroll(TerminalScroll::Bottom);
-                            None
-                        } else if is_app_cursor {
-                            ss3("F", mod_no)
-                        } else {
-                            csi("F", "", mod_no)
-                        }
-                    }
-                    Named::Home => {
-                        if modifiers.shift() {
-                            terminal.scroll(TerminalScroll::Top);
-                            None
-                        } else if is_app_cursor {
-                            ss3("H", mod_no)
-                        } else {
-                            csi("H", "", mod_no)
-                        }
-                    }
-                    Named::F1 => ss3("P", mod_no),
-                    Named::F2 => ss3("Q", mod_no),
-                    Named::F3 => ss3("R", mod_no),
-                    Named::F4 => ss3("S", mod_no),
-                    Named::F5 => csi("15", "~", mod_no),
-                    Named::F6 => csi("17", "~", mod_no),
-                    Named::F7 => csi("18", "~", mod_no),
-                    Named::F8 => csi("19", "~", mod_no),
-                    Named::F9 => csi("20", "~", mod_no),
-                    Named::F10 => csi("21", "~", mod_no),
-                    Named::F11 => csi("23", "~", mod_no),
-                    Named::F12 => csi("24", "~", mod_no),
-                    _ => None,
-                };
-                if let Some(escape_code) = escape_code {
-                    terminal.input_scroll(escape_code);
+
+                // Copy mode keys are handled by the application and never sent to the pty
+                if terminal.is_copy_mode() {
                     return Status::Captured;
                 }
 
-                //Special handle Enter, Escape, Backspace and Tab as described in
-                //https://sw.kovidgoyal.net/kitty/keyboard-protocol/#legacy-key-event-encoding
-                //Also special handle Ctrl-_ to behave like xterm
-                let alt_prefix = if modifiers.alt() { "\x1B" } else { "" };
-                match named {
-                    Named::Backspace => {
-                        let code = if modifiers.control() { "\x08" } else { "\x7f" };
-                        terminal.input_scroll(format!("{alt_prefix}{code}").as_bytes().to_vec());
-                        status = Status::Captured;
-                    }
-                    Named::Enter => {
-                        terminal
-                            .input_scroll(format!("{}{}", alt_prefix, "\x0D").as_bytes().to_vec());
-                        status = Status::Captured;
-                    }
-                    Named::Escape => {
-                        //Escape with any modifier will cancel selection
-                        let had_selection = {
-                            let mut term = terminal.term.lock();
-                            term.selection.take().is_some()
-                        };
-                        if had_selection {
-                            terminal.update();
-                        } else {
-                            terminal.input_scroll(
-                                format!("{}{}", alt_prefix, "\x1B").as_bytes().to_vec(),
-                            );
-                        }
-                        status = Status::Captured;
-                    }
-                    Named::Space => {
-                        terminal.input_scroll(format!("{}{}", alt_prefix, " ").as_bytes().to_vec());
-                        status = Status::Captured;
-                    }
-                    Named::Tab => {
-                        let code = if modifiers.shift() { "\x1b[Z" } else { "\x09" };
-                        terminal.input_scroll(format!("{alt_prefix}{code}").as_bytes().to_vec());
-                        status = Status::Captured;
+                // Shift with navigation keys scrolls the scrollback instead of sending input
+                if modifiers.shift() {
+                    let scroll_opt = match &key {
+                        Key::Named(Named::PageUp) => Some(TerminalScroll::PageUp),
+                        Key::Named(Named::PageDown) => Some(TerminalScroll::PageDown),
+                        Key::Named(Named::Home) => Some(TerminalScroll::Top),
+                        Key::Named(Named::End) => Some(TerminalScroll::Bottom),
+                        _ => None,
+                    };
+                    if let Some(scroll) = scroll_opt {
+                        terminal.scroll(scroll);
+                        return Status::Captured;
                     }
-                    _ => {}
                 }
-            }
-            Event::Keyboard(KeyEvent::ModifiersChanged(modifiers)) => {
-                state.modifiers = modifiers;
-            }
-            Event::Keyboard(KeyEvent::KeyPressed {
-                text,
-                modifiers,
-                key,
-                ..
-            }) if state.is_focused => {
-                for key_bind in self.key_binds.keys() {
-                    if key_bind.matches(modifiers, &key) {
+
+                //Escape with any modifier will cancel selection
+                if key == Key::Named(Named::Escape) {
+                    let had_selection = {
+                        let mut term = terminal.term.lock();
+                        term.selection.take().is_some()
+                    };
+                    if had_selection {
+                        terminal.update();
                         return Status::Captured;
                     }
                 }
-                let character = text.and_then(|c| c.chars().next()).unwrap_or_default();
-                match (
-                    modifiers.logo(),
-                    modifiers.control(),
-                    modifiers.alt(),
-                    modifiers.shift(),
-                ) {
-                    (true, _, _, _) => {
-                        // Ignore super
-                    }
-                    (false, true, true, _) => {
-                        // Handle ctrl-alt for non-control characters
-                        // and control characters 0-32
-                        if !character.is_control() || (character as u32) < 32 {
-                            // Handle alt for non-control characters
-                            let mut buf = [0x1B, 0, 0, 0, 0];
-                            let len = {
-                                let str = character.encode_utf8(&mut buf[1..]);
-                                str.len() + 1
-                            };
-                            terminal.input_scroll(buf[..len].to_vec());
-                            status = Status::Captured;
-                        }
-                    }
-                    (false, true, _, false) => {
-                        // Handle ctrl for control characters (Ctrl-A to Ctrl-Z)
-                        if character.is_control() {
-                            let mut buf = [0, 0, 0, 0];
-                            let str = character.encode_utf8(&mut buf);
-                            terminal.input_scroll(str.as_bytes().to_vec());
-                            status = Status::Captured;
-                        }
-                    }
-                    (false, true, _, true) => {
-                        //This is normally Ctrl+Minus, but since that
-                        //is taken by zoom, we send that code for
-                        //Ctrl+Underline instead, like xterm and
-                        //gnome-terminal
-                        if key == Key::Character("_".into()) {
-                            terminal.input_scroll(b"\x1F".as_slice());
-                            status = Status::Captured;
-                        }
-                    }
-                    (false, false, true, _) => {
-                        if !character.is_control() {
-                            // Handle alt for non-control characters
-                            let mut buf = [0x1B, 0, 0, 0, 0];
-                            let len = {
-                                let str = character.encode_utf8(&mut buf[1..]);
-                                str.len() + 1
-                            };
-                            terminal.input_scroll(buf[..len].to_vec());
-                            status = Status::Captured;
-                        }
-                    }
-                    (false, false, false, _) => {
-                        // Handle no modifiers for non-control characters
-                        if !character.is_control() {
-                            let mut buf = [0, 0, 0, 0];
-                            let str = character.encode_utf8(&mut buf);
-                            terminal.input_scroll(str.as_bytes().to_vec());
-                            status = Status::Captured;
-                        }
-                    }
+
+                let mode = *terminal.term.lock().mode();
+                if let Some(input) =
+                    key_encoder::encode(&key, location, text.as_deref(), modifiers, mode)
+                {
+                    terminal.input_scroll(input);
+                    status = Status::Captured;
                 }
             }
+            Event::Keyboard(KeyEvent::ModifiersChanged(modifiers)) => {
+                state.modifiers = modifiers;
+            }
             Event::Mouse(MouseEvent::ButtonPressed(button)) => {
                 if let Some(p) = cursor_position.position_in(layout.bounds()) {
                     let x = p.x - self.padding.left;
@@ -870,7 +760,14 @@ where
                                 };
                                 let selection = match click_kind {
                                     ClickKind::Single => {
-                                        Selection::new(SelectionType::Simple, location, side)
+                                        // Alt+drag selects a rectangular block, copying it
+                                        // trims the trailing padding of each row
+                                        let selection_type = if state.modifiers.alt() {
+                                            SelectionType::Block
+                                        } else {
+                                            SelectionType::Simple
+                                        };
+                                        Selection::new(selection_type, location, side)
                                     }
                                     ClickKind::Double => {
                                         Selection::new(SelectionType::Semantic, location, side)
@@ -1067,16 +964,67 @@ fn shade(color: cosmic_text::Color, is_focused: bool) -> cosmic_text::Color {
     if is_focused {
         color
     } else {
-        let shade = 0.92;
         cosmic_text::Color::rgba(
-            (f32::from(color.r()) * shade) as u8,
-            (f32::from(color.g()) * shade) as u8,
-            (f32::from(color.b()) * shade) as u8,
+            (f32::from(color.r()) * UNFOCUSED_SHADE) as u8,
+            (f32::from(color.g()) * UNFOCUSED_SHADE) as u8,
+            (f32::from(color.b()) * UNFOCUSED_SHADE) as u8,
             color.a(),
         )
     }
 }
 
+fn draw_background_image(
+    renderer: &mut Renderer,
+    handle: image::Handle,
+    mode: BackgroundImageMode,
+    bounds: Rectangle,
+) {
+    let size = renderer.dimensions(&handle);
+    if size.width == 0 || size.height == 0 {
+        // Image not loaded or invalid
+        return;
+    }
+    let image_size = Size::new(size.width as f32, size.height as f32);
+
+    match mode {
+        BackgroundImageMode::Scaled => {
+            let scale = (bounds.width / image_size.width).max(bounds.height / image_size.height);
+            let scaled_size = Size::new(image_size.width * scale, image_size.height * scale);
+            let position =
+                bounds.center() - Vector::new(scaled_size.width / 2.0, scaled_size.height / 2.0);
+            renderer.draw(
+                handle,
+                FilterMethod::Linear,
+                Rectangle::new(position, scaled_size),
+            );
+        }
+        BackgroundImageMode::Tiled => {
+            let mut y = bounds.y;
+            while y < bounds.y + bounds.height {
+                let mut x = bounds.x;
+                while x < bounds.x + bounds.width {
+                    renderer.draw(
+                        handle.clone(),
+                        FilterMethod::Nearest,
+                        Rectangle::new(Point::new(x, y), image_size),
+                    );
+                    x += image_size.width;
+                }
+                y += image_size.height;
+            }
+        }
+        BackgroundImageMode::Centered => {
+            let position =
+                bounds.center() - Vector::new(image_size.width / 2.0, image_size.height / 2.0);
+            renderer.draw(
+                handle,
+                FilterMethod::Linear,
+                Rectangle::new(position, image_size),
+            );
+        }
+    }
+}
+
 impl<'a, Message> From<TerminalBox<'a, Message>> for Element<'a, Message, cosmic::Theme, Renderer>
 where
     Message: Clone + 'a,
@@ -1136,52 +1084,3 @@ impl operation::Focusable for State {
         self.is_focused = false;
     }
 }
-
-/*
- shift     0b1         (1)
-alt       0b10        (2)
-ctrl      0b100       (4)
-super     0b1000      (8)
-hyper     0b10000     (16)
-meta      0b100000    (32)
-caps_lock 0b1000000   (64)
-num_lock  0b10000000  (128)
-*/
-fn calculate_modifier_number(state: &State) -> u8 {
-    let mut mod_no = 0;
-    if state.modifiers.shift() {
-        mod_no |= 1;
-    }
-    if state.modifiers.alt() {
-        mod_no |= 2;
-    }
-    if state.modifiers.control() {
-        mod_no |= 4;
-    }
-    if state.modifiers.logo() {
-        mod_no |= 8;
-    }
-    mod_no + 1
-}
-
-#[inline(always)]
-fn csi(code: &str, suffix: &str, modifiers: u8) -> Option<Vec<u8>> {
-    if modifiers == 1 {
-        Some(format!("\x1B[{code}{suffix}").as_bytes().to_vec())
-    } else {
-        Some(
-            format!("\x1B[{code};{modifiers}{suffix}")
-                .as_bytes()
-                .to_vec(),
-        )
-    }
-}
-
-#[inline(always)]
-fn ss3(code: &str, modifiers: u8) -> Option<Vec<u8>> {
-    if modifiers == 1 {
-        Some(format!("\x1B\x4F{code}").as_bytes().to_vec())
-    } else {
-        Some(format!("\x1B[1;{modifiers}{code}").as_bytes().to_vec())
-    }
-}
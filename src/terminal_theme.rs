@@ -2,16 +2,26 @@ use alacritty_terminal::{
     term::color::Colors,
     vte::ansi::{NamedColor, Rgb},
 };
+use cosmic::{cosmic_theme, iced::Color, theme};
 use hex_color::HexColor;
 use palette::{encoding::Srgb, rgb::Rgb as PRgb, FromColor, Okhsl};
-use std::{collections::HashMap, fs};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use crate::config::{
-    ColorScheme, ColorSchemeAnsi, ColorSchemeKind, COSMIC_THEME_DARK, COSMIC_THEME_LIGHT,
+    ColorScheme, ColorSchemeAnsi, ColorSchemeKind, COSMIC_THEME_DARK, COSMIC_THEME_DESKTOP,
+    COSMIC_THEME_LIGHT,
 };
 
+/// Minimum contrast of colors generated from the desktop theme against its background
+const DESKTOP_MIN_CONTRAST: f64 = 4.5;
+/// Minimum contrast of the cursor generated from the desktop theme, as a non-text element
+const DESKTOP_MIN_CURSOR_CONTRAST: f64 = 3.0;
+
 // Fill missing dim/bright colors with derived values from normal ones.
-#[allow(dead_code)]
 struct ColorDerive {
     dim_saturation_adjustment: f32,
     dim_lightness_adjustment: f32,
@@ -19,7 +29,6 @@ struct ColorDerive {
     bright_lightness_adjustment: f32,
 }
 
-#[allow(dead_code)]
 impl ColorDerive {
     fn new() -> Self {
         Self {
@@ -73,15 +82,15 @@ impl ColorDerive {
         Self::color_adj(rgb, saturation_adj, lightness_adj)
     }
 
+    // Colors without a normal color to derive from are left unset
     fn fill_missing_brights(&self, colors: &mut Colors) {
         macro_rules! populate {
             ($($normal:ident$(,)?)+) => {
                 paste::paste!{
                     $(
                         if colors[NamedColor::[<Bright $normal>]].is_none() {
-                            match colors[NamedColor::$normal] {
-                                None => panic!("tried to derive bright color from {} which is not set", stringify!($normal)),
-                                Some(rgb) => colors[NamedColor::[<Bright $normal>]] = Some(self.brighten(rgb)),
+                            if let Some(rgb) = colors[NamedColor::$normal] {
+                                colors[NamedColor::[<Bright $normal>]] = Some(self.brighten(rgb));
                             }
                         }
                     )+
@@ -92,15 +101,15 @@ impl ColorDerive {
         populate! { Foreground, Black, Red, Green, Yellow, Blue, Magenta, Cyan, White };
     }
 
+    // Colors without a normal color to derive from are left unset
     fn fill_missing_dims(&self, colors: &mut Colors) {
         macro_rules! populate {
             ($($normal:ident$(,)?)+) => {
                 paste::paste!{
                     $(
                         if colors[NamedColor::[<Dim $normal>]].is_none() {
-                            match colors[NamedColor::$normal] {
-                                None => panic!("tried to derive dim color from {} which is not set", stringify!($normal)),
-                                Some(rgb) => colors[NamedColor::[<Dim $normal>]] = Some(self.dim_and_faint(rgb)),
+                            if let Some(rgb) = colors[NamedColor::$normal] {
+                                colors[NamedColor::[<Dim $normal>]] = Some(self.dim_and_faint(rgb));
                             }
                         }
                     )+
@@ -112,6 +121,73 @@ impl ColorDerive {
     }
 }
 
+/// Adjust the lightness of a foreground color until its contrast ratio against the background
+/// reaches the minimum, preferring to move away from the background lightness. A foreground equal
+/// to the background is kept, as it is used to hide text.
+pub fn ensure_contrast(fg: Rgb, bg: Rgb, minimum_contrast: f64) -> Rgb {
+    if fg == bg || fg.contrast(bg) >= minimum_contrast {
+        return fg;
+    }
+
+    let fg_okhsl = ColorDerive::rgb_to_okhsl(fg);
+    let bg_okhsl = ColorDerive::rgb_to_okhsl(bg);
+    let with_lightness = |lightness: f32| {
+        ColorDerive::okhsl_to_rgb(Okhsl {
+            lightness,
+            ..fg_okhsl
+        })
+    };
+
+    // Find the smallest lightness change towards the limit that meets the minimum contrast
+    let adjust = |limit: f32| -> Option<Rgb> {
+        let limit_rgb = with_lightness(limit);
+        if limit_rgb.contrast(bg) < minimum_contrast {
+            return None;
+        }
+        let mut failing = fg_okhsl.lightness;
+        let mut passing = limit;
+        for _ in 0..10 {
+            let lightness = (failing + passing) / 2.0;
+            if with_lightness(lightness).contrast(bg) >= minimum_contrast {
+                passing = lightness;
+            } else {
+                failing = lightness;
+            }
+        }
+        Some(with_lightness(passing))
+    };
+
+    let limits = if bg_okhsl.lightness < 0.5 {
+        [1.0, 0.0]
+    } else {
+        [0.0, 1.0]
+    };
+    limits
+        .into_iter()
+        .find_map(adjust)
+        // The minimum cannot be met, so use the best possible contrast
+        .unwrap_or_else(|| {
+            let (lighter, darker) = (with_lightness(1.0), with_lightness(0.0));
+            if lighter.contrast(bg) >= darker.contrast(bg) {
+                lighter
+            } else {
+                darker
+            }
+        })
+}
+
+// Get the color derivation used for a kind of color scheme
+fn color_derive(color_scheme_kind: ColorSchemeKind) -> ColorDerive {
+    match color_scheme_kind {
+        ColorSchemeKind::Dark => ColorDerive::new(),
+        // With light backgrounds, the dim and faint descriptions are at odds!
+        // To make the color fainter, we would need to increase lightness not decrease it!
+        // But other terminals seem to still dim colors in light themes. So we dim too, but
+        // not by much, since normal colors are dim enough already.
+        ColorSchemeKind::Light => ColorDerive::new().with_dim_lightness_adjustment(-0.07),
+    }
+}
+
 fn auto_colors() -> Colors {
     let mut colors = Colors::default();
 
@@ -194,10 +270,77 @@ impl From<&ColorScheme> for Colors {
         colors[NamedColor::BrightForeground] = encode_rgb(color_scheme.bright_foreground);
         colors[NamedColor::DimForeground] = encode_rgb(color_scheme.dim_foreground);
 
+        // Fill missing bright and dim colors, which would otherwise be drawn black. Colors set by
+        // the color scheme are never changed.
+        let color_derive = color_derive(color_scheme_kind(&colors));
+        color_derive.fill_missing_brights(&mut colors);
+        color_derive.fill_missing_dims(&mut colors);
+
         colors
     }
 }
 
+/// Colors of highlighted cells, which have no slot in the terminal colors. Unset colors are
+/// derived from the terminal colors when rendering.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HighlightColors {
+    pub selection_foreground: Option<Rgb>,
+    pub selection_background: Option<Rgb>,
+    pub search_match_foreground: Option<Rgb>,
+    pub search_match_background: Option<Rgb>,
+    pub focused_match_foreground: Option<Rgb>,
+    pub focused_match_background: Option<Rgb>,
+    pub hyperlink: Option<Rgb>,
+}
+
+impl From<&ColorScheme> for HighlightColors {
+    fn from(color_scheme: &ColorScheme) -> Self {
+        let encode_rgb = |rgb_opt: Option<HexColor>| -> Option<Rgb> {
+            let rgb = rgb_opt?;
+            Some(Rgb {
+                r: rgb.r,
+                g: rgb.g,
+                b: rgb.b,
+            })
+        };
+
+        Self {
+            selection_foreground: encode_rgb(color_scheme.selection_foreground),
+            selection_background: encode_rgb(color_scheme.selection_background),
+            search_match_foreground: encode_rgb(color_scheme.search_match_foreground),
+            search_match_background: encode_rgb(color_scheme.search_match_background),
+            focused_match_foreground: encode_rgb(color_scheme.focused_match_foreground),
+            focused_match_background: encode_rgb(color_scheme.focused_match_background),
+            hyperlink: encode_rgb(color_scheme.hyperlink),
+        }
+    }
+}
+
+/// Terminal colors along with the highlight colors of a color scheme
+#[derive(Clone, Copy)]
+pub struct TerminalTheme {
+    pub colors: Colors,
+    pub highlight_colors: HighlightColors,
+}
+
+impl From<Colors> for TerminalTheme {
+    fn from(colors: Colors) -> Self {
+        Self {
+            colors,
+            highlight_colors: HighlightColors::default(),
+        }
+    }
+}
+
+impl From<&ColorScheme> for TerminalTheme {
+    fn from(color_scheme: &ColorScheme) -> Self {
+        Self {
+            colors: Colors::from(color_scheme),
+            highlight_colors: HighlightColors::from(color_scheme),
+        }
+    }
+}
+
 impl From<(&str, &Colors)> for ColorScheme {
     fn from(tuple: (&str, &Colors)) -> Self {
         let (name, colors) = tuple;
@@ -244,6 +387,7 @@ impl From<(&str, &Colors)> for ColorScheme {
                 cyan: encode_rgb(colors[NamedColor::DimCyan]),
                 white: encode_rgb(colors[NamedColor::DimWhite]),
             },
+            ..Default::default()
         }
     }
 }
@@ -284,7 +428,7 @@ pub fn cosmic_dark() -> Colors {
     colors[NamedColor::BrightForeground] = colors[NamedColor::BrightWhite];
 
     // Fill missing dim colors
-    ColorDerive::new().fill_missing_dims(&mut colors);
+    color_derive(ColorSchemeKind::Dark).fill_missing_dims(&mut colors);
 
     colors
 }
@@ -325,28 +469,222 @@ pub fn cosmic_light() -> Colors {
     colors[NamedColor::BrightForeground] = colors[NamedColor::BrightBlack];
 
     // Fill missing dim colors
-    ColorDerive::new()
-        // With light backgrounds, the dim and faint descriptions are at odds!
-        // To make the color fainter, we would need to increase lightness not decrease it!
-        // But other terminals seem to still dim colors in light themes. So we dim too, but
-        // not by much, since normal colors are dim enough already.
-        .with_dim_lightness_adjustment(-0.07)
-        .fill_missing_dims(&mut colors);
+    color_derive(ColorSchemeKind::Light).fill_missing_dims(&mut colors);
+
+    colors
+}
+
+/// Generate colors from the palette and accent color of a desktop theme, the semantic colors are
+/// used for red, green and yellow while the other hues follow the accent color
+pub fn cosmic_desktop(theme: &cosmic_theme::Theme) -> Colors {
+    let mut colors = auto_colors();
+
+    let encode_rgb = |color: cosmic_theme::palette::Srgba| -> Rgb {
+        let [r, g, b, _] = Color::from(color).into_rgba8();
+        Rgb { r, g, b }
+    };
+
+    let background = encode_rgb(theme.background.base);
+    let contrasting = |rgb: Rgb| {
+        // Unlike in color schemes, generated colors equal to the background are not meant to hide
+        // text, which happens when bright white is derived in light themes
+        let rgb = if rgb == background {
+            ColorDerive::color_adj(rgb, 0.0, -0.01)
+        } else {
+            rgb
+        };
+        ensure_contrast(rgb, background, DESKTOP_MIN_CONTRAST)
+    };
+    let foreground = contrasting(encode_rgb(theme.background.on));
+    let accent = encode_rgb(theme.accent_color());
+    let accent_okhsl = ColorDerive::rgb_to_okhsl(accent);
+    let with_hue = |hue: f32| {
+        contrasting(ColorDerive::okhsl_to_rgb(Okhsl {
+            hue: hue.into(),
+            ..accent_okhsl
+        }))
+    };
+
+    // Black and white are the darkest and lightest colors, whatever the theme
+    let (black, white) = if theme.is_dark {
+        (
+            ColorDerive::color_adj(background, 0.0, 0.1),
+            contrasting(ColorDerive::color_adj(foreground, 0.0, -0.1)),
+        )
+    } else {
+        (
+            contrasting(ColorDerive::color_adj(foreground, 0.0, 0.1)),
+            ColorDerive::color_adj(background, 0.0, -0.1),
+        )
+    };
+
+    colors[NamedColor::Black] = Some(black);
+    colors[NamedColor::Red] = Some(contrasting(encode_rgb(theme.destructive_color())));
+    colors[NamedColor::Green] = Some(contrasting(encode_rgb(theme.success_color())));
+    colors[NamedColor::Yellow] = Some(contrasting(encode_rgb(theme.warning_color())));
+    colors[NamedColor::Blue] = Some(with_hue(255.0));
+    colors[NamedColor::Magenta] = Some(with_hue(325.0));
+    colors[NamedColor::Cyan] = Some(with_hue(195.0));
+    colors[NamedColor::White] = Some(white);
+
+    // Set special colors
+    colors[NamedColor::Foreground] = Some(foreground);
+    colors[NamedColor::Background] = Some(background);
+    colors[NamedColor::Cursor] = Some(ensure_contrast(
+        accent,
+        background,
+        DESKTOP_MIN_CURSOR_CONTRAST,
+    ));
+
+    // Fill missing bright and dim colors, keeping bright colors readable
+    let color_derive = color_derive(if theme.is_dark {
+        ColorSchemeKind::Dark
+    } else {
+        ColorSchemeKind::Light
+    });
+    color_derive.fill_missing_brights(&mut colors);
+    for index in NamedColor::BrightBlack as usize..=NamedColor::BrightWhite as usize {
+        colors[index] = colors[index].map(contrasting);
+    }
+    color_derive.fill_missing_dims(&mut colors);
 
     colors
 }
 
-// Get builtin themes
-pub fn terminal_themes() -> HashMap<(String, ColorSchemeKind), Colors> {
+// Background of the desktop theme of a kind, for color schemes without a background that do not
+// match the application theme
+pub fn window_background(color_scheme_kind: ColorSchemeKind) -> Rgb {
+    let theme = match color_scheme_kind {
+        ColorSchemeKind::Dark => theme::system_dark(),
+        ColorSchemeKind::Light => theme::system_light(),
+    };
+    let [r, g, b, _] = Color::from(theme.cosmic().background.base).into_rgba8();
+    Rgb { r, g, b }
+}
+
+// Get builtin themes, including those generated from the desktop themes
+pub fn terminal_themes() -> HashMap<(String, ColorSchemeKind), TerminalTheme> {
     let mut themes = HashMap::new();
     themes.insert(
         (COSMIC_THEME_DARK.to_string(), ColorSchemeKind::Dark),
-        cosmic_dark(),
+        cosmic_dark().into(),
     );
     themes.insert(
         (COSMIC_THEME_LIGHT.to_string(), ColorSchemeKind::Light),
-        cosmic_light(),
+        cosmic_light().into(),
     );
+    themes.insert(
+        (COSMIC_THEME_DESKTOP.to_string(), ColorSchemeKind::Dark),
+        cosmic_desktop(theme::system_dark().cosmic()).into(),
+    );
+    themes.insert(
+        (COSMIC_THEME_DESKTOP.to_string(), ColorSchemeKind::Light),
+        cosmic_desktop(theme::system_light().cosmic()).into(),
+    );
+    themes
+}
+
+// Infer the kind of a color scheme from the luminance of its background, picking whichever of
+// black or white has the better contrast against it
+pub fn color_scheme_kind(colors: &Colors) -> ColorSchemeKind {
+    let Some(background) = colors[NamedColor::Background] else {
+        return ColorSchemeKind::Dark;
+    };
+    let black = Rgb { r: 0, g: 0, b: 0 };
+    let white = Rgb {
+        r: 0xFF,
+        g: 0xFF,
+        b: 0xFF,
+    };
+    if background.contrast(black) > background.contrast(white) {
+        ColorSchemeKind::Light
+    } else {
+        ColorSchemeKind::Dark
+    }
+}
+
+// Directories containing color scheme files, in order of priority
+pub fn color_scheme_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    match xdg::BaseDirectories::with_prefix("cosmic-term") {
+        Ok(xdg_dirs) => {
+            dirs.push(xdg_dirs.get_data_home().join("color-schemes"));
+            for data_dir in xdg_dirs.get_data_dirs() {
+                dirs.push(data_dir.join("color-schemes"));
+            }
+        }
+        Err(err) => {
+            log::warn!("failed to get XDG base directories: {}", err);
+        }
+    }
+    dirs
+}
+
+/// Paths to watch for changes to the color scheme directories. Directories that do not exist
+/// yet are watched through their closest existing parent so that their creation is noticed.
+pub fn color_scheme_watch_paths(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for dir in dirs {
+        if let Some(path) = dir.ancestors().find(|path| path.is_dir()) {
+            if !paths.iter().any(|other| other == path) {
+                paths.push(path.to_path_buf());
+            }
+        }
+    }
+    paths
+}
+
+fn load_color_scheme(path: &Path) -> Result<ColorScheme, String> {
+    let mut file = fs::File::open(path).map_err(|err| format!("Failed to open {path:?}: {err}"))?;
+    let mut color_scheme: ColorScheme = ron::de::from_reader(&mut file)
+        .map_err(|err| format!("Failed to parse {path:?}: {err}"))?;
+    if color_scheme.name.is_empty() {
+        if let Some(file_stem) = path.file_stem() {
+            color_scheme.name = file_stem.to_string_lossy().into_owned();
+        }
+    }
+    Ok(color_scheme)
+}
+
+// Load themes from color scheme directories, with earlier directories taking priority
+pub fn color_scheme_dir_themes(
+    errors: &mut Vec<String>,
+) -> HashMap<(String, ColorSchemeKind), TerminalTheme> {
+    let mut themes = HashMap::new();
+    for dir in color_scheme_dirs() {
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(ok) => ok,
+            Err(err) => {
+                if err.kind() != io::ErrorKind::NotFound {
+                    log::warn!("failed to read color scheme directory {:?}: {}", dir, err);
+                }
+                continue;
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = read_dir
+            .filter_map(|entry_res| entry_res.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "ron"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match load_color_scheme(&path) {
+                Ok(color_scheme) => {
+                    let theme = TerminalTheme::from(&color_scheme);
+                    let color_scheme_kind = color_scheme_kind(&theme.colors);
+                    themes
+                        .entry((color_scheme.name, color_scheme_kind))
+                        .or_insert(theme);
+                }
+                Err(err) => {
+                    log::warn!("{}", err);
+                    errors.push(err);
+                }
+            }
+        }
+    }
     themes
 }
 
@@ -354,13 +692,18 @@ pub fn terminal_themes() -> HashMap<(String, ColorSchemeKind), Colors> {
 #[allow(dead_code)]
 pub fn export() {
     for ((name, _color_scheme_kind), theme) in terminal_themes() {
-        let color_scheme = ColorScheme::from((name.as_str(), &theme));
+        // Desktop themes are generated
+        if name == COSMIC_THEME_DESKTOP {
+            continue;
+        }
+
+        let color_scheme = ColorScheme::from((name.as_str(), &theme.colors));
 
         // Ensure conversion to and from ColorScheme matches original theme
         {
             let theme_conv = Colors::from(&color_scheme);
             for i in 0..alacritty_terminal::term::color::COUNT {
-                assert_eq!(theme[i], theme_conv[i]);
+                assert_eq!(theme.colors[i], theme_conv[i]);
             }
         }
 
@@ -383,3 +726,197 @@ pub fn export() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(value: u32) -> Option<HexColor> {
+        Some(HexColor::rgb(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ))
+    }
+
+    fn rgb(hex_color_opt: Option<HexColor>) -> Option<Rgb> {
+        hex_color_opt.map(|hex_color| Rgb {
+            r: hex_color.r,
+            g: hex_color.g,
+            b: hex_color.b,
+        })
+    }
+
+    fn ansi(first: u32) -> ColorSchemeAnsi {
+        let mut ansi = ColorSchemeAnsi::default();
+        for index in 0..8 {
+            ansi.set(index, hex(first + index as u32 * 0x101010));
+        }
+        ansi
+    }
+
+    fn from_hex(value: u32) -> Rgb {
+        rgb(hex(value)).unwrap()
+    }
+
+    fn hue(rgb: Rgb) -> f32 {
+        ColorDerive::rgb_to_okhsl(rgb).hue.into_positive_degrees()
+    }
+
+    #[test]
+    fn contrast_is_met() {
+        for (fg, bg) in [
+            // Dark on dark
+            (0x203a80, 0x101010),
+            (0x602020, 0x282828),
+            // Light on light
+            (0xe0c860, 0xffffff),
+            (0x90e0a0, 0xf0f0f0),
+            // Similar lightness
+            (0x7a3a3a, 0x505050),
+        ] {
+            let (fg, bg) = (from_hex(fg), from_hex(bg));
+            for minimum_contrast in [3.0, 4.5, 7.0] {
+                let adjusted = ensure_contrast(fg, bg, minimum_contrast);
+                assert!(
+                    adjusted.contrast(bg) >= minimum_contrast,
+                    "{:?} on {:?} has contrast {} below {}",
+                    adjusted,
+                    bg,
+                    adjusted.contrast(bg),
+                    minimum_contrast
+                );
+
+                // Only the lightness changes, allowing for rounding to 8 bit components
+                let difference = (hue(adjusted) - hue(fg)).abs();
+                assert!(
+                    difference.min(360.0 - difference) < 3.0,
+                    "hue of {:?} changed to {:?}",
+                    fg,
+                    adjusted
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn desktop_contrast_is_met() {
+        for theme in [
+            cosmic_theme::Theme::dark_default(),
+            cosmic_theme::Theme::light_default(),
+        ] {
+            let colors = cosmic_desktop(&theme);
+            let background = colors[NamedColor::Background].unwrap();
+
+            // Black or white is close to the background on purpose, like in other color schemes
+            let background_like = if theme.is_dark {
+                NamedColor::Black
+            } else {
+                NamedColor::White
+            };
+            let indexes = (NamedColor::Black as usize..=NamedColor::BrightWhite as usize)
+                .filter(|index| *index != background_like as usize)
+                .chain([NamedColor::Foreground as usize]);
+            for index in indexes {
+                let rgb = colors[index].unwrap();
+                assert!(
+                    rgb.contrast(background) >= DESKTOP_MIN_CONTRAST,
+                    "color {} {:?} on {:?}, dark {}",
+                    index,
+                    rgb,
+                    background,
+                    theme.is_dark
+                );
+            }
+
+            let cursor = colors[NamedColor::Cursor].unwrap();
+            assert!(cursor.contrast(background) >= DESKTOP_MIN_CURSOR_CONTRAST);
+        }
+    }
+
+    #[test]
+    fn contrast_unchanged() {
+        // Already meets the minimum
+        let (fg, bg) = (from_hex(0xd8d8d8), from_hex(0x181818));
+        assert_eq!(ensure_contrast(fg, bg, 4.5), fg);
+
+        // Hidden text uses the background as the foreground
+        for color in [0x000000, 0x808080, 0xffffff] {
+            let color = from_hex(color);
+            assert_eq!(ensure_contrast(color, color, 4.5), color);
+        }
+    }
+
+    #[test]
+    fn contrast_unreachable() {
+        // Black has the better contrast against a middle gray
+        let adjusted = ensure_contrast(from_hex(0x4080c0), from_hex(0x777777), 21.0);
+        assert_eq!(adjusted, from_hex(0x000000));
+
+        // White has the better contrast against a darker gray
+        let adjusted = ensure_contrast(from_hex(0x4080c0), from_hex(0x555555), 21.0);
+        assert_eq!(adjusted, from_hex(0xffffff));
+    }
+
+    #[test]
+    fn scheme_colors_are_kept() {
+        let color_scheme = ColorScheme {
+            name: "Complete".to_string(),
+            foreground: hex(0xd8d8d8),
+            background: hex(0x181818),
+            cursor: hex(0xff8000),
+            bright_foreground: hex(0xf8f8f8),
+            dim_foreground: hex(0x808080),
+            normal: ansi(0x100000),
+            bright: ansi(0x000010),
+            dim: ansi(0x001000),
+            ..Default::default()
+        };
+        let colors = Colors::from(&color_scheme);
+        assert_eq!(ColorScheme::from(("Complete", &colors)), color_scheme);
+    }
+
+    #[test]
+    fn missing_colors_are_derived() {
+        let mut color_scheme = ColorScheme {
+            foreground: hex(0xd8d8d8),
+            background: hex(0x181818),
+            normal: ansi(0x400000),
+            ..Default::default()
+        };
+        color_scheme.normal.red = None;
+        color_scheme.bright.green = hex(0x00ff00);
+        color_scheme.dim.blue = hex(0x000080);
+
+        let colors = Colors::from(&color_scheme);
+        let color_derive = color_derive(ColorSchemeKind::Dark);
+        for (normal, bright, dim) in [
+            (
+                NamedColor::Foreground,
+                NamedColor::BrightForeground,
+                NamedColor::DimForeground,
+            ),
+            (
+                NamedColor::Black,
+                NamedColor::BrightBlack,
+                NamedColor::DimBlack,
+            ),
+            (
+                NamedColor::Cyan,
+                NamedColor::BrightCyan,
+                NamedColor::DimCyan,
+            ),
+        ] {
+            let rgb = colors[normal].unwrap();
+            assert_eq!(colors[bright], Some(color_derive.brighten(rgb)));
+            assert_eq!(colors[dim], Some(color_derive.dim_and_faint(rgb)));
+        }
+
+        // Set colors are kept, colors without a normal color stay unset
+        assert_eq!(colors[NamedColor::BrightGreen], rgb(hex(0x00ff00)));
+        assert_eq!(colors[NamedColor::DimBlue], rgb(hex(0x000080)));
+        assert_eq!(colors[NamedColor::Red], None);
+        assert_eq!(colors[NamedColor::BrightRed], None);
+        assert_eq!(colors[NamedColor::DimRed], None);
+    }
+}